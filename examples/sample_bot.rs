@@ -0,0 +1,121 @@
+//! A minimal bot for the external bot protocol described in `src/bot.rs`. It pushes its most
+//! advanced pawns one square at a time and otherwise passes, which keeps every move legal without
+//! knowing the rest of the rules.
+//!
+//! Run a game against it with
+//! `cargo build --example sample_bot && cargo run -- --black-bot target/debug/examples/sample_bot`.
+
+use std::io::{self, BufRead, Write};
+
+const PROTOCOL_VERSION: &str = "1";
+
+struct Position {
+    white_to_move: bool,
+    actions: usize,
+    /// Occupied squares, indexed by file then rank, both from zero.
+    occupied: [[bool; 8]; 8],
+    /// Own pawns as file and rank.
+    pawns: Vec<(usize, usize)>,
+}
+
+fn parse_position(line: &str) -> Option<Position> {
+    let mut tokens = line.split_whitespace().skip(1);
+    let white_to_move = tokens.next()? == "white";
+    let actions = tokens.next()?.parse().ok()?;
+
+    let mut position = Position {
+        white_to_move,
+        actions,
+        occupied: [[false; 8]; 8],
+        pawns: Vec::new(),
+    };
+
+    // Pieces look like `wPe2:50:10`.
+    for token in tokens {
+        let &[color, kind, file @ b'a'..=b'h', rank @ b'1'..=b'8', ..] = token.as_bytes() else {
+            return None;
+        };
+        let (file, rank) = ((file - b'a') as usize, (rank - b'1') as usize);
+
+        position.occupied[file][rank] = true;
+
+        let own = (color == b'w') == white_to_move;
+        if own && kind == b'P' {
+            position.pawns.push((file, rank));
+        }
+    }
+
+    Some(position)
+}
+
+fn choose_moves(position: &mut Position) -> Vec<String> {
+    let white = position.white_to_move;
+    let forward = |rank: usize| {
+        if white {
+            rank.checked_add(1).filter(|rank| *rank < 8)
+        } else {
+            rank.checked_sub(1)
+        }
+    };
+
+    // Most advanced first.
+    position
+        .pawns
+        .sort_by_key(|(_, rank)| if white { 7 - rank } else { *rank });
+
+    let mut moves = Vec::new();
+
+    for (file, rank) in position.pawns.clone() {
+        if moves.len() == position.actions {
+            break;
+        }
+
+        let Some(next) = forward(rank) else {
+            continue;
+        };
+
+        if position.occupied[file][next] {
+            continue;
+        }
+
+        position.occupied[file][rank] = false;
+        position.occupied[file][next] = true;
+
+        let file = (b'a' + file as u8) as char;
+        moves.push(format!("{file}{}{file}{}", rank + 1, next + 1));
+    }
+
+    moves
+}
+
+fn main() -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let mut position = None;
+
+    for line in stdin.lock().lines() {
+        let line = line?;
+        let command = line.split_whitespace().next().unwrap_or_default();
+
+        match command {
+            "fdc" => {
+                if line.split_whitespace().nth(1) != Some(PROTOCOL_VERSION) {
+                    eprintln!("sample_bot: unsupported protocol version: {line}");
+                }
+                writeln!(stdout, "id name Sample Bot")?;
+                writeln!(stdout, "ready")?;
+            }
+            "position" => position = parse_position(&line),
+            "go" => {
+                let moves = position.as_mut().map(choose_moves).unwrap_or_default();
+                writeln!(stdout, "moves {}", moves.join(" "))?;
+            }
+            "quit" => break,
+            _ => {}
+        }
+
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,408 @@
+//! Input actions and the keys bound to them. Systems ask whether an [`Action`] was just pressed
+//! instead of checking a fixed key, so players can rebind keys from the settings page, and prompts
+//! name whichever key is bound through [`Bindings::prompt`]. [`Actions`] also reads the gamepad,
+//! so prompts name pad buttons while a pad is in use.
+
+use crate::{
+    Typewriter,
+    gamepad::{self, LastDevice, StickTap},
+    settings::Settings,
+};
+use bevy::{ecs::system::SystemParam, prelude::*, ui::UiSystems};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    /// Starts a game, continues dialogue and dismisses end-of-game screens.
+    Confirm,
+    /// Clears the selection, opens the pause menu and steps back out of menus.
+    Deselect,
+    Pass,
+    /// Starts a puzzle or a lost level over.
+    Retry,
+    ToggleHealthBars,
+    ToggleThreats,
+    ToggleCoverage,
+    ToggleDebug,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::Confirm,
+        Action::Deselect,
+        Action::Pass,
+        Action::Retry,
+        Action::ToggleHealthBars,
+        Action::ToggleThreats,
+        Action::ToggleCoverage,
+        Action::ToggleDebug,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Confirm => "Confirm",
+            Action::Deselect => "Deselect / Back",
+            Action::Pass => "Pass",
+            Action::Retry => "Retry",
+            Action::ToggleHealthBars => "Health Bars",
+            Action::ToggleThreats => "Threats",
+            Action::ToggleCoverage => "Coverage",
+            Action::ToggleDebug => "Debug UI",
+        }
+    }
+
+    pub fn default_key(self) -> KeyCode {
+        match self {
+            Action::Confirm => KeyCode::Space,
+            Action::Deselect => KeyCode::Escape,
+            Action::Pass => KeyCode::KeyP,
+            Action::Retry => KeyCode::KeyR,
+            Action::ToggleHealthBars => KeyCode::KeyH,
+            Action::ToggleThreats => KeyCode::KeyT,
+            Action::ToggleCoverage => KeyCode::KeyG,
+            Action::ToggleDebug => KeyCode::Backquote,
+        }
+    }
+
+    /// The pad button standing for the action, for those that have one.
+    pub fn button(self) -> Option<GamepadButton> {
+        match self {
+            Action::Confirm => Some(GamepadButton::South),
+            Action::Deselect => Some(GamepadButton::East),
+            Action::Pass => Some(GamepadButton::North),
+            Action::Retry => Some(GamepadButton::West),
+            _ => None,
+        }
+    }
+
+    /// Whether `action` can be bound to `key`, which must not already have a fixed meaning on a
+    /// screen the action is read on. Only the board reads the board actions, while the others
+    /// work everywhere, including the lobby where the address is typed.
+    pub fn can_bind(self, key: KeyCode) -> bool {
+        let fixed: &[KeyCode] = match self {
+            Action::Confirm | Action::Deselect | Action::ToggleDebug => &LOBBY_KEYS,
+            Action::Pass
+            | Action::Retry
+            | Action::ToggleHealthBars
+            | Action::ToggleThreats
+            | Action::ToggleCoverage => &[],
+        };
+
+        BINDABLE.contains(&key) && !fixed.contains(&key)
+    }
+}
+
+/// Moving the board cursor and the menu focus. These keys and buttons can't be rebound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nav {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Tab, or Shift+Tab for [`Nav::Previous`].
+    Next,
+    Previous,
+    Select,
+}
+
+impl Nav {
+    fn keys(self) -> &'static [KeyCode] {
+        match self {
+            Nav::Up => &[KeyCode::ArrowUp, KeyCode::KeyW],
+            Nav::Down => &[KeyCode::ArrowDown, KeyCode::KeyS],
+            Nav::Left => &[KeyCode::ArrowLeft, KeyCode::KeyA],
+            Nav::Right => &[KeyCode::ArrowRight, KeyCode::KeyD],
+            Nav::Next | Nav::Previous => &[KeyCode::Tab],
+            Nav::Select => &[KeyCode::Enter, KeyCode::NumpadEnter],
+        }
+    }
+
+    fn button(self) -> GamepadButton {
+        match self {
+            Nav::Up => GamepadButton::DPadUp,
+            Nav::Down => GamepadButton::DPadDown,
+            Nav::Left => GamepadButton::DPadLeft,
+            Nav::Right => GamepadButton::DPadRight,
+            Nav::Next => GamepadButton::RightTrigger,
+            Nav::Previous => GamepadButton::LeftTrigger,
+            Nav::Select => GamepadButton::South,
+        }
+    }
+}
+
+/// Keys an action can be bound to. The arrow keys, WASD, Tab and Enter always move the board cursor
+/// and the menu focus, so they are left out.
+pub const BINDABLE: [KeyCode; 62] = [
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::Space,
+    KeyCode::Escape,
+    KeyCode::Backspace,
+    KeyCode::Backquote,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::BracketLeft,
+    KeyCode::BracketRight,
+    KeyCode::Backslash,
+    KeyCode::Semicolon,
+    KeyCode::Quote,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Insert,
+    KeyCode::Delete,
+    KeyCode::Home,
+    KeyCode::End,
+];
+
+/// Keys typing the address in the LAN lobby.
+const LOBBY_KEYS: [KeyCode; 13] = [
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::Period,
+    KeyCode::Semicolon,
+    KeyCode::Backspace,
+];
+
+/// Keys are stored by their [`KeyCode`] name, such as `KeyP`.
+fn key_name(key: KeyCode) -> String {
+    format!("{key:?}")
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    BINDABLE.into_iter().find(|key| key_name(*key) == name)
+}
+
+/// How a key reads in prompts, such as `P` or `SPACE`.
+pub fn key_label(key: KeyCode) -> String {
+    let name = key_name(key);
+
+    match key {
+        KeyCode::Escape => "ESC".to_string(),
+        KeyCode::Backquote => "`".to_string(),
+        _ => name
+            .strip_prefix("Key")
+            .or_else(|| name.strip_prefix("Digit"))
+            .unwrap_or(&name)
+            .to_uppercase(),
+    }
+}
+
+/// Actions are stored by their variant name, such as `Pass`.
+fn action_name(action: Action) -> String {
+    format!("{action:?}")
+}
+
+/// Keys bound to each action, both by name. Actions missing from the map use their default key,
+/// and names no action goes by, from another version, are ignored.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Bindings(BTreeMap<String, String>);
+
+impl FromIterator<(Action, String)> for Bindings {
+    fn from_iter<I: IntoIterator<Item = (Action, String)>>(bindings: I) -> Self {
+        Self(
+            bindings
+                .into_iter()
+                .map(|(action, key)| (action_name(action), key))
+                .collect(),
+        )
+    }
+}
+
+impl Bindings {
+    /// The key bound to `action`. A key it can't be bound to, from a file edited by hand or
+    /// written by an older version, falls back to the default.
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.0
+            .get(&action_name(action))
+            .and_then(|name| key_from_name(name))
+            .filter(|key| action.can_bind(*key))
+            .unwrap_or(action.default_key())
+    }
+
+    /// The bound key in brackets, as prompts show it.
+    pub fn prompt(&self, action: Action) -> String {
+        format!("[{}]", key_label(self.key(action)))
+    }
+
+    /// Binds `key` to `action`, returning whether it could. An action already bound to `key`
+    /// takes over `action`'s old key, so no two actions ever share one. Keys either action can't
+    /// be bound to, as told by [`Action::can_bind`], leave the bindings as they were.
+    pub fn bind(&mut self, action: Action, key: KeyCode) -> bool {
+        if !action.can_bind(key) {
+            return false;
+        }
+
+        let old = self.key(action);
+
+        if let Some(other) = Action::ALL
+            .into_iter()
+            .find(|other| *other != action && self.key(*other) == key)
+        {
+            if !other.can_bind(old) {
+                return false;
+            }
+            self.set(other, old);
+        }
+
+        self.set(action, key);
+        true
+    }
+
+    fn set(&mut self, action: Action, key: KeyCode) {
+        if key == action.default_key() {
+            self.0.remove(&action_name(action));
+        } else {
+            self.0.insert(action_name(action), key_name(key));
+        }
+    }
+}
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(PostUpdate, update_prompts.before(UiSystems::Prepare));
+}
+
+/// The keyboard read through the current bindings, and the gamepads.
+#[derive(SystemParam)]
+pub struct Actions<'w, 's> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    settings: Res<'w, Settings>,
+    device: Res<'w, LastDevice>,
+    stick: Res<'w, StickTap>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl Actions<'_, '_> {
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.keys.just_pressed(self.settings.bindings.key(action))
+            || action
+                .button()
+                .is_some_and(|button| self.button_just_pressed(button))
+    }
+
+    pub fn nav_just_pressed(&self, nav: Nav) -> bool {
+        let shift = self
+            .keys
+            .any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+        let key = match nav {
+            Nav::Next if shift => false,
+            Nav::Previous if !shift => false,
+            _ => self.keys.any_just_pressed(nav.keys().iter().copied()),
+        };
+
+        key || self.button_just_pressed(nav.button()) || self.stick.0 == Some(nav)
+    }
+
+    fn button_just_pressed(&self, button: GamepadButton) -> bool {
+        self.gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(button))
+    }
+
+    /// The bound key in brackets, or the pad button while a pad is in use.
+    pub fn prompt(&self, action: Action) -> String {
+        match action.button() {
+            Some(button) if *self.device == LastDevice::Gamepad => {
+                format!("[{}]", gamepad::glyph(button))
+            }
+            _ => self.settings.bindings.prompt(action),
+        }
+    }
+
+    /// `text` with placeholders such as `{pass}` replaced by the prompt of the action they name.
+    pub fn fill_prompts(&self, text: &str) -> String {
+        Action::ALL
+            .into_iter()
+            .fold(text.to_string(), |text, action| {
+                let placeholder = format!("{{{action:?}}}").to_lowercase();
+                text.replace(&placeholder, &self.prompt(action))
+            })
+    }
+}
+
+/// A text built from prompts, rebuilt when the bindings or the device in use change. Texts that
+/// are rebuilt every frame anyway can call [`Actions::prompt`] directly.
+#[derive(Component)]
+pub struct PromptText(pub fn(&Actions) -> String);
+
+fn update_prompts(
+    mut prompts: Query<(Ref<PromptText>, &mut Text, Option<&mut Typewriter>)>,
+    actions: Actions,
+) {
+    let changed = actions.settings.is_changed() || actions.device.is_changed();
+
+    for (prompt, mut text, writer) in &mut prompts {
+        if !changed && !prompt.is_added() {
+            continue;
+        }
+
+        let full_text = (prompt.0)(&actions);
+
+        match writer {
+            // A typewriter keeps typing the new text from where it got to.
+            Some(mut writer) => {
+                writer.visible_chars = writer.visible_chars.min(full_text.len());
+                text.0 = full_text[..writer.visible_chars].to_string();
+                writer.full_text = full_text;
+            }
+            None => text.0 = full_text,
+        }
+    }
+}
+
+/// Run condition for systems that only react to `action`.
+pub fn action_just_pressed(action: Action) -> impl FnMut(Actions) -> bool + Clone {
+    move |actions: Actions| actions.just_pressed(action)
+}
@@ -0,0 +1,82 @@
+//! Computer opponents. Each [`Opponent`] picks moves from a [`Board`] snapshot, one at a time.
+
+use crate::behaviour::{Move, *};
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::Deserialize;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Opponent>();
+}
+
+#[derive(Resource, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Opponent {
+    /// Moves a random piece to a random square.
+    #[default]
+    Random,
+    /// Never moves, only attacks what wanders into range.
+    Passive,
+    /// Picks the move that deals the most damage next attack phase while taking the least.
+    Aggressive,
+    /// A second player at the same machine, see the hotseat mode.
+    Human,
+    /// A second player over the network, see the LAN mode.
+    Remote,
+    /// A separate program given on the command line, see [`crate::bot`].
+    External,
+}
+
+impl Opponent {
+    /// The names [`Opponent::from_name`] accepts.
+    pub const NAMES: [&str; 3] = ["random", "passive", "aggressive"];
+
+    /// The computer opponent called `name`, ignoring case, as given on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "random" => Some(Opponent::Random),
+            "passive" => Some(Opponent::Passive),
+            "aggressive" => Some(Opponent::Aggressive),
+            _ => None,
+        }
+    }
+
+    /// Picks the next move for `color`, or `None` if it doesn't move or isn't a computer.
+    pub fn choose_move(self, board: &Board, color: PieceColor, rng: &mut impl Rng) -> Option<Move> {
+        let moves = board.moves_for(color);
+
+        match self {
+            Opponent::Random => moves.choose(rng).copied(),
+            Opponent::Passive | Opponent::Human | Opponent::Remote | Opponent::External => None,
+            Opponent::Aggressive => {
+                let mut best = Vec::new();
+                let mut best_score = f32::MIN;
+
+                for (from, to) in moves {
+                    let mut next = *board;
+                    next.move_piece(from, to);
+
+                    let score = damage_dealt(&next, color) - damage_dealt(&next, color.opponent());
+
+                    if score > best_score {
+                        best_score = score;
+                        best.clear();
+                    }
+
+                    if score == best_score {
+                        best.push((from, to));
+                    }
+                }
+
+                best.choose(rng).copied()
+            }
+        }
+    }
+}
+
+fn damage_dealt(board: &Board, attacker: PieceColor) -> f32 {
+    board
+        .damage_events(attacker)
+        .iter()
+        .map(|hit| hit.damage)
+        .sum()
+}
@@ -1,3 +1,4 @@
+use crate::{campaign::Campaign, puzzle::PuzzlePack};
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
 use bevy_seedling::sample::AudioSample;
@@ -47,3 +48,15 @@ pub struct SoundsCollection {
     #[asset(path = "sound/vineboom.wav")]
     pub vineboom: Handle<AudioSample>,
 }
+
+#[derive(AssetCollection, Resource)]
+pub struct PuzzlesCollection {
+    #[asset(path = "puzzles/main.pack.ron")]
+    pub pack: Handle<PuzzlePack>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct CampaignCollection {
+    #[asset(path = "campaign/main.campaign.ron")]
+    pub campaign: Handle<Campaign>,
+}
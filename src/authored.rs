@@ -0,0 +1,127 @@
+//! Authored boards such as puzzles and campaign levels: pieces placed by hand and the goal to
+//! meet on them.
+
+use crate::{ai::Opponent, behaviour::*};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+/// A piece placed by an authored board.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct PlacedPiece {
+    pub x: i32,
+    pub y: i32,
+    pub color: PieceColor,
+    pub kind: PieceKind,
+    /// Starting health, if different from the kind's maximum.
+    #[serde(default)]
+    pub health: Option<f32>,
+}
+
+/// Builds a board out of placed pieces, skipping any that are out of bounds.
+pub fn board_from(name: &str, pieces: &[PlacedPiece]) -> Board {
+    let mut board = Board::default();
+
+    for placed in pieces {
+        if !GridCoords::new(placed.x, placed.y).in_bounds() {
+            warn!(
+                "{name:?} places a piece out of bounds at ({}, {})",
+                placed.x, placed.y
+            );
+            continue;
+        }
+
+        board.set(
+            GridCoords::new(placed.x, placed.y),
+            Some(Piece {
+                health: placed.health.unwrap_or(placed.kind.max_health()),
+                ..Piece::new(placed.color, placed.kind)
+            }),
+        );
+    }
+
+    board
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// Kill the black king within `passes` passes.
+    KillKing { passes: u8 },
+    /// Pass `turns` times without losing a single white piece.
+    Survive { turns: u8 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Won,
+    Lost,
+}
+
+impl Goal {
+    pub fn description(self) -> String {
+        match self {
+            Goal::KillKing { passes } => format!(
+                "Kill the Black King\nwithin {passes} {}",
+                if passes == 1 { "pass" } else { "passes" }
+            ),
+            Goal::Survive { turns } => {
+                format!("Survive {turns} enemy turns\nwithout losing a piece")
+            }
+        }
+    }
+
+    pub fn limit(self) -> u8 {
+        match self {
+            Goal::KillKing { passes } => passes,
+            Goal::Survive { turns } => turns,
+        }
+    }
+
+    /// Checks the goal against the pieces left on the board after `passes` passes. Returns `None`
+    /// while the goal is still undecided.
+    pub fn outcome(
+        self,
+        passes: u8,
+        starting_white: usize,
+        pieces: impl IntoIterator<Item = Piece>,
+    ) -> Option<Outcome> {
+        let mut king_alive = false;
+        let mut white = 0;
+
+        for piece in pieces {
+            match piece.color {
+                PieceColor::White => white += 1,
+                PieceColor::Black => king_alive |= piece.kind == PieceKind::King,
+            }
+        }
+
+        match self {
+            Goal::KillKing { .. } if !king_alive => Some(Outcome::Won),
+            Goal::Survive { .. } if white < starting_white => Some(Outcome::Lost),
+            Goal::KillKing { .. } if passes >= self.limit() => Some(Outcome::Lost),
+            Goal::Survive { .. } if passes >= self.limit() => Some(Outcome::Won),
+            _ => None,
+        }
+    }
+}
+
+/// A position with a goal, as read from a `.puzzle.ron` file.
+#[derive(Asset, TypePath, Deserialize, Debug, Clone)]
+pub struct Puzzle {
+    pub name: String,
+    pub goal: Goal,
+    #[serde(default)]
+    pub opponent: Opponent,
+    pub pieces: Vec<PlacedPiece>,
+}
+
+impl Puzzle {
+    /// Reads a puzzle file directly rather than through the asset server, for tools and tests.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, BevyError> {
+        Ok(ron::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn board(&self) -> Board {
+        board_from(&self.name, &self.pieces)
+    }
+}
@@ -1,4 +1,10 @@
 use bevy::{platform::collections::HashSet, prelude::*};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
 
 pub const PAWN_HEALTH: f32 = 50.0;
 pub const KNIGHT_HEALTH: f32 = 100.0;
@@ -7,15 +13,84 @@ pub const ROOK_HEALTH: f32 = 200.0;
 pub const QUEEN_HEALTH: f32 = 150.0;
 pub const KING_HEALTH: f32 = 300.0;
 
-#[derive(Component, Reflect, Copy, Clone, Debug)]
+/// Damage a piece deals to each piece in range when its side passes, unless upgraded.
+pub const DAMAGE: f32 = 10.0;
+
+/// Experience needed for each level, so reaching level 3 takes twice as much as level 2.
+pub const XP_PER_LEVEL: u32 = 50;
+/// Extra experience for landing a killing blow, on top of the damage dealt.
+pub const KILL_XP: u32 = 20;
+pub const MAX_LEVEL: u8 = 5;
+/// Max health gained per level.
+pub const LEVEL_HEALTH: f32 = 20.0;
+/// Damage gained per level.
+pub const LEVEL_DAMAGE: f32 = 2.0;
+
+#[derive(Component, Reflect, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[reflect(Component)]
 pub struct Piece {
     pub color: PieceColor,
     pub kind: PieceKind,
     pub health: f32,
+    pub damage: f32,
+    /// Clamped to `1..=MAX_LEVEL` when read, as pieces also arrive from other players and bots.
+    #[serde(deserialize_with = "deserialize_level")]
+    pub level: u8,
+    pub xp: u32,
+}
+
+fn deserialize_level<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    Ok(u8::deserialize(deserializer)?.clamp(1, MAX_LEVEL))
+}
+
+impl Piece {
+    /// A piece at full health dealing the default [`DAMAGE`].
+    pub fn new(color: PieceColor, kind: PieceKind) -> Self {
+        Self {
+            color,
+            kind,
+            health: kind.max_health(),
+            damage: DAMAGE,
+            level: 1,
+            xp: 0,
+        }
+    }
+
+    pub fn max_health(&self) -> f32 {
+        self.kind.max_health() + f32::from(self.level.saturating_sub(1)) * LEVEL_HEALTH
+    }
+
+    /// Adds experience, levelling up as many times as it allows. Each level raises damage and
+    /// heals the piece by the max health it gains. Returns whether the piece levelled up.
+    pub fn gain_xp(&mut self, xp: u32) -> bool {
+        let level = self.level;
+        self.xp += xp;
+
+        while self.level < MAX_LEVEL && self.xp >= u32::from(self.level) * XP_PER_LEVEL {
+            self.level += 1;
+            self.damage += LEVEL_DAMAGE;
+            self.health += LEVEL_HEALTH;
+        }
+
+        self.level > level
+    }
 }
 
-#[derive(Reflect, Copy, Clone, Debug, PartialEq, Eq)]
+// Health and damage are never NaN, so pieces can be compared and hashed by their bits.
+impl Eq for Piece {}
+
+impl Hash for Piece {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.color.hash(state);
+        self.kind.hash(state);
+        self.health.to_bits().hash(state);
+        self.damage.to_bits().hash(state);
+        self.level.hash(state);
+        self.xp.hash(state);
+    }
+}
+
+#[derive(Reflect, Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PieceKind {
     Pawn,
     Knight,
@@ -25,12 +100,46 @@ pub enum PieceKind {
     King,
 }
 
-#[derive(Reflect, Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Reflect, Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PieceColor {
     White,
     Black,
 }
 
+impl PieceColor {
+    pub fn opponent(self) -> Self {
+        match self {
+            PieceColor::White => PieceColor::Black,
+            PieceColor::Black => PieceColor::White,
+        }
+    }
+}
+
+impl PieceKind {
+    pub fn max_health(self) -> f32 {
+        match self {
+            PieceKind::Pawn => PAWN_HEALTH,
+            PieceKind::Knight => KNIGHT_HEALTH,
+            PieceKind::Bishop => BISHOP_HEALTH,
+            PieceKind::Rook => ROOK_HEALTH,
+            PieceKind::Queen => QUEEN_HEALTH,
+            PieceKind::King => KING_HEALTH,
+        }
+    }
+
+    /// The usual notation letter, `N` for knights.
+    pub fn letter(self) -> char {
+        match self {
+            PieceKind::Pawn => 'P',
+            PieceKind::Knight => 'N',
+            PieceKind::Bishop => 'B',
+            PieceKind::Rook => 'R',
+            PieceKind::Queen => 'Q',
+            PieceKind::King => 'K',
+        }
+    }
+}
+
 #[derive(Debug, Component, Clone, Copy, Reflect, PartialEq, Eq, Hash)]
 #[reflect(Component)]
 pub struct GridCoords(pub IVec2);
@@ -45,6 +154,35 @@ impl GridCoords {
     }
 }
 
+/// Formats coordinates as a square name, with `y == 0` being black's back rank: `(4, 6)` is `e2`.
+impl fmt::Display for GridCoords {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.0.x as u8) as char, 8 - self.0.y)
+    }
+}
+
+/// Parses square names like `e2`, the inverse of the [`fmt::Display`] impl.
+impl FromStr for GridCoords {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let &[file, rank] = s.as_bytes() else {
+            return Err(());
+        };
+
+        let coords = GridCoords::new(
+            i32::from(file.to_ascii_lowercase()) - i32::from(b'a'),
+            8 - (i32::from(rank) - i32::from(b'0')),
+        );
+
+        if coords.in_bounds() {
+            Ok(coords)
+        } else {
+            Err(())
+        }
+    }
+}
+
 #[derive(Resource, Debug, Reflect, Default, Clone, Copy)]
 #[reflect(Resource)]
 pub struct ChessGrid {
@@ -60,11 +198,276 @@ impl ChessGrid {
     pub fn get_piece(&self, GridCoords(IVec2 { x, y }): GridCoords) -> Option<Entity> {
         self.pieces[x as usize][y as usize]
     }
+
+    /// Copies the pieces on the grid into a [`Board`], looking each one up with `piece`.
+    pub fn to_board(self, piece: impl Fn(Entity) -> Option<Piece>) -> Board {
+        let mut board = Board::default();
+
+        for x in 0..8 {
+            for y in 0..8 {
+                board.0[x][y] = self.pieces[x][y].and_then(&piece);
+            }
+        }
+
+        board
+    }
+}
+
+/// The pieces on either back rank of the standard layout, by file.
+const BACK_RANK: [PieceKind; 8] = [
+    PieceKind::Rook,
+    PieceKind::Knight,
+    PieceKind::Bishop,
+    PieceKind::Queen,
+    PieceKind::King,
+    PieceKind::Bishop,
+    PieceKind::Knight,
+    PieceKind::Rook,
+];
+
+/// A move from one square to another.
+pub type Move = (GridCoords, GridCoords);
+
+/// A snapshot of the pieces on the board, detached from the ECS so turns can be simulated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Board(pub [[Option<Piece>; 8]; 8]);
+
+impl Board {
+    /// The standard chess starting position.
+    pub fn standard() -> Self {
+        let mut board = Self::default();
+
+        for (file, kind) in board.0.iter_mut().zip(BACK_RANK) {
+            file[0] = Some(Piece::new(PieceColor::Black, kind));
+            file[1] = Some(Piece::new(PieceColor::Black, PieceKind::Pawn));
+            file[6] = Some(Piece::new(PieceColor::White, PieceKind::Pawn));
+            file[7] = Some(Piece::new(PieceColor::White, kind));
+        }
+
+        board
+    }
+
+    pub fn get(&self, GridCoords(IVec2 { x, y }): GridCoords) -> Option<Piece> {
+        self.0[x as usize][y as usize]
+    }
+
+    pub fn get_mut(&mut self, GridCoords(IVec2 { x, y }): GridCoords) -> Option<&mut Piece> {
+        self.0[x as usize][y as usize].as_mut()
+    }
+
+    pub fn set(&mut self, GridCoords(IVec2 { x, y }): GridCoords, piece: Option<Piece>) {
+        self.0[x as usize][y as usize] = piece;
+    }
+
+    pub fn pieces(&self) -> impl Iterator<Item = (GridCoords, Piece)> + '_ {
+        (0..8).flat_map(move |x| {
+            (0..8).filter_map(move |y| {
+                let pos = GridCoords::new(x, y);
+                self.get(pos).map(|piece| (pos, piece))
+            })
+        })
+    }
+
+    pub fn legal_moves(&self, pos: GridCoords) -> HashSet<GridCoords> {
+        match self.get(pos) {
+            Some(piece) => legal_moves(piece, pos, *self),
+            None => HashSet::default(),
+        }
+    }
+
+    /// Every legal move for `color`'s pieces.
+    pub fn moves_for(&self, color: PieceColor) -> Vec<Move> {
+        self.pieces()
+            .filter(|(_, piece)| piece.color == color)
+            .flat_map(|(from, _)| self.legal_moves(from).into_iter().map(move |to| (from, to)))
+            .collect()
+    }
+
+    pub fn move_piece(&mut self, from: GridCoords, to: GridCoords) {
+        let piece = self.get(from);
+        self.set(from, None);
+        self.set(to, piece);
+    }
+
+    /// Every hit dealt by `attacker`'s pieces in one attack phase. A piece in range of several
+    /// attackers is hit once per attacker.
+    pub fn damage_events(&self, attacker: PieceColor) -> Vec<Hit> {
+        let mut events = Vec::new();
+
+        for (from, piece) in self.pieces() {
+            if piece.color != attacker {
+                continue;
+            }
+
+            for target in attacks(piece, from, *self) {
+                if self
+                    .get(target)
+                    .is_some_and(|target| target.color != attacker)
+                {
+                    events.push(Hit {
+                        attacker: from,
+                        target,
+                        damage: piece.damage,
+                    });
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Damage `attacker`'s pieces would deal to each square in one attack phase if an enemy stood
+    /// there, indexed like [`Board::get`]. Squares held by `attacker` are never hit.
+    pub fn threat_map(&self, attacker: PieceColor) -> [[f32; 8]; 8] {
+        let mut map = [[0.0; 8]; 8];
+
+        for (from, piece) in self.pieces() {
+            if piece.color != attacker {
+                continue;
+            }
+
+            for target in attack_pattern(piece, from, *self) {
+                map[target.0.x as usize][target.0.y as usize] += piece.damage;
+            }
+        }
+
+        map
+    }
+
+    /// Runs `attacker`'s attack phase and removes the pieces it kills, returning their squares.
+    pub fn apply_damage(&mut self, attacker: PieceColor) -> Vec<GridCoords> {
+        for hit in self.damage_events(attacker) {
+            if let Some(piece) = self.get_mut(hit.target) {
+                piece.health -= hit.damage;
+            }
+        }
+
+        self.remove_dead()
+    }
+
+    /// Like [`Board::apply_damage`], but attackers also gain experience as they do in the game: the
+    /// damage they deal, plus [`KILL_XP`] for a killing blow.
+    pub fn apply_damage_with_xp(&mut self, attacker: PieceColor) -> Vec<GridCoords> {
+        for hit in self.damage_events(attacker) {
+            let mut xp = hit.damage as u32;
+
+            if let Some(piece) = self.get_mut(hit.target) {
+                let was_alive = piece.health > 0.0;
+                piece.health -= hit.damage;
+
+                if was_alive && piece.health <= 0.0 {
+                    xp += KILL_XP;
+                }
+            }
+
+            if let Some(piece) = self.get_mut(hit.attacker) {
+                piece.gain_xp(xp);
+            }
+        }
+
+        self.remove_dead()
+    }
+
+    fn remove_dead(&mut self) -> Vec<GridCoords> {
+        let killed: Vec<GridCoords> = self
+            .pieces()
+            .filter(|(_, piece)| piece.health <= 0.0)
+            .map(|(pos, _)| pos)
+            .collect();
+
+        for pos in &killed {
+            self.set(*pos, None);
+        }
+
+        killed
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub attacker: GridCoords,
+    pub target: GridCoords,
+    pub damage: f32,
+}
+
+pub trait Occupancy: Copy {
+    fn is_occupied(&self, pos: GridCoords) -> bool;
+}
+
+impl Occupancy for ChessGrid {
+    fn is_occupied(&self, pos: GridCoords) -> bool {
+        self.get_piece(pos).is_some()
+    }
+}
+
+impl Occupancy for Board {
+    fn is_occupied(&self, pos: GridCoords) -> bool {
+        self.get(pos).is_some()
+    }
+}
+
+/// A board with something on every square, so every square in reach counts as attacked.
+#[derive(Clone, Copy)]
+struct Filled;
+
+impl Occupancy for Filled {
+    fn is_occupied(&self, _pos: GridCoords) -> bool {
+        true
+    }
 }
 
 pub trait PieceBehaviour {
-    fn get_legal_moves(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords>;
-    fn get_attacks(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords>;
+    fn get_legal_moves(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords>;
+    fn get_attacks(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords>;
+}
+
+/// Legal moves for `piece` standing on `pos`, picking the behaviour from its kind and color.
+pub fn legal_moves(piece: Piece, pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
+    match piece.kind {
+        PieceKind::Pawn => match piece.color {
+            PieceColor::White => WhitePawnBehaviour::get_legal_moves(pos, grid),
+            PieceColor::Black => BlackPawnBehaviour::get_legal_moves(pos, grid),
+        },
+        PieceKind::Knight => KnightBehaviour::get_legal_moves(pos, grid),
+        PieceKind::Bishop => BishopBehaviour::get_legal_moves(pos, grid),
+        PieceKind::Rook => RookBehaviour::get_legal_moves(pos, grid),
+        PieceKind::Queen => QueenBehaviour::get_legal_moves(pos, grid),
+        PieceKind::King => KingBehaviour::get_legal_moves(pos, grid),
+    }
+}
+
+/// Squares attacked by `piece` standing on `pos`, picking the behaviour from its kind and color.
+pub fn attacks(piece: Piece, pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
+    match piece.kind {
+        PieceKind::Pawn => match piece.color {
+            PieceColor::White => WhitePawnBehaviour::get_attacks(pos, grid),
+            PieceColor::Black => BlackPawnBehaviour::get_attacks(pos, grid),
+        },
+        PieceKind::Knight => KnightBehaviour::get_attacks(pos, grid),
+        PieceKind::Bishop => BishopBehaviour::get_attacks(pos, grid),
+        PieceKind::Rook => RookBehaviour::get_attacks(pos, grid),
+        PieceKind::Queen => QueenBehaviour::get_attacks(pos, grid),
+        PieceKind::King => KingBehaviour::get_attacks(pos, grid),
+    }
+}
+
+/// Squares `piece` standing on `pos` would hit if an enemy stood there. Unlike [`attacks`], pawns,
+/// knights and kings reach empty squares too, while sliding pieces are still stopped by whatever
+/// is in their way. Squares held by the piece's own side are left out.
+pub fn attack_pattern(piece: Piece, pos: GridCoords, board: Board) -> HashSet<GridCoords> {
+    let reach = match piece.kind {
+        PieceKind::Bishop | PieceKind::Rook | PieceKind::Queen => attacks(piece, pos, board),
+        PieceKind::Pawn | PieceKind::Knight | PieceKind::King => attacks(piece, pos, Filled),
+    };
+
+    reach
+        .into_iter()
+        .filter(|target| {
+            board
+                .get(*target)
+                .is_none_or(|target| target.color != piece.color)
+        })
+        .collect()
 }
 
 pub struct WhitePawnBehaviour;
@@ -80,23 +483,23 @@ impl WhitePawnBehaviour {
 }
 
 impl PieceBehaviour for WhitePawnBehaviour {
-    fn get_legal_moves(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_legal_moves(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         let mut moves = HashSet::default();
         let potential = GridCoords(pos.0 - IVec2::Y);
 
-        if potential.in_bounds() && grid.get_piece(potential).is_none() {
+        if potential.in_bounds() && !grid.is_occupied(potential) {
             moves.insert(potential);
         }
 
         moves
     }
 
-    fn get_attacks(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_attacks(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         let mut attack = HashSet::default();
 
         for offset in Self::ATTACKS {
             let potential = GridCoords(pos.0 + offset);
-            if potential.in_bounds() && grid.get_piece(potential).is_some() {
+            if potential.in_bounds() && grid.is_occupied(potential) {
                 attack.insert(potential);
             }
         }
@@ -110,23 +513,23 @@ impl BlackPawnBehaviour {
 }
 
 impl PieceBehaviour for BlackPawnBehaviour {
-    fn get_legal_moves(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_legal_moves(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         let mut moves = HashSet::default();
         let potential = GridCoords(pos.0 + IVec2::Y);
 
-        if potential.in_bounds() && grid.get_piece(potential).is_none() {
+        if potential.in_bounds() && !grid.is_occupied(potential) {
             moves.insert(potential);
         }
 
         moves
     }
 
-    fn get_attacks(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_attacks(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         let mut attack = HashSet::default();
 
         for offset in Self::ATTACKS {
             let potential = GridCoords(pos.0 + offset);
-            if potential.in_bounds() && grid.get_piece(potential).is_some() {
+            if potential.in_bounds() && grid.is_occupied(potential) {
                 attack.insert(potential);
             }
         }
@@ -149,12 +552,12 @@ impl KnightBehaviour {
 }
 
 impl PieceBehaviour for KnightBehaviour {
-    fn get_legal_moves(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_legal_moves(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         let mut moves = HashSet::default();
 
         for offset in Self::OFFSETS {
             let potential = GridCoords(pos.0 + offset);
-            if potential.in_bounds() && grid.get_piece(potential).is_none() {
+            if potential.in_bounds() && !grid.is_occupied(potential) {
                 moves.insert(potential);
             }
         }
@@ -162,12 +565,12 @@ impl PieceBehaviour for KnightBehaviour {
         moves
     }
 
-    fn get_attacks(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_attacks(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         let mut attack = HashSet::default();
 
         for offset in Self::OFFSETS {
             let potential = GridCoords(pos.0 + offset);
-            if potential.in_bounds() && grid.get_piece(potential).is_some() {
+            if potential.in_bounds() && grid.is_occupied(potential) {
                 attack.insert(potential);
             }
         }
@@ -186,11 +589,11 @@ impl BishopBehaviour {
 }
 
 impl PieceBehaviour for BishopBehaviour {
-    fn get_legal_moves(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_legal_moves(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         sliding_moves(pos, grid, &Self::DIRECTIONS)
     }
 
-    fn get_attacks(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_attacks(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         sliding_attacks(pos, grid, &Self::DIRECTIONS)
     }
 }
@@ -200,11 +603,11 @@ impl RookBehaviour {
 }
 
 impl PieceBehaviour for RookBehaviour {
-    fn get_legal_moves(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_legal_moves(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         sliding_moves(pos, grid, &Self::DIRECTIONS)
     }
 
-    fn get_attacks(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_attacks(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         sliding_attacks(pos, grid, &Self::DIRECTIONS)
     }
 }
@@ -223,11 +626,11 @@ impl QueenBehaviour {
 }
 
 impl PieceBehaviour for QueenBehaviour {
-    fn get_legal_moves(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_legal_moves(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         sliding_moves(pos, grid, &Self::DIRECTIONS)
     }
 
-    fn get_attacks(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_attacks(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         sliding_attacks(pos, grid, &Self::DIRECTIONS)
     }
 }
@@ -246,12 +649,12 @@ impl KingBehaviour {
 }
 
 impl PieceBehaviour for KingBehaviour {
-    fn get_legal_moves(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_legal_moves(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         let mut moves = HashSet::default();
 
         for offset in Self::OFFSETS {
             let potential = GridCoords(pos.0 + offset);
-            if potential.in_bounds() && grid.get_piece(potential).is_none() {
+            if potential.in_bounds() && !grid.is_occupied(potential) {
                 moves.insert(potential);
             }
         }
@@ -259,12 +662,12 @@ impl PieceBehaviour for KingBehaviour {
         moves
     }
 
-    fn get_attacks(pos: GridCoords, grid: ChessGrid) -> HashSet<GridCoords> {
+    fn get_attacks(pos: GridCoords, grid: impl Occupancy) -> HashSet<GridCoords> {
         let mut attacks = HashSet::default();
 
         for offset in Self::OFFSETS {
             let potential = GridCoords(pos.0 + offset);
-            if potential.in_bounds() && grid.get_piece(potential).is_some() {
+            if potential.in_bounds() && grid.is_occupied(potential) {
                 attacks.insert(potential);
             }
         }
@@ -273,7 +676,11 @@ impl PieceBehaviour for KingBehaviour {
     }
 }
 
-fn sliding_moves(pos: GridCoords, grid: ChessGrid, directions: &[IVec2]) -> HashSet<GridCoords> {
+fn sliding_moves(
+    pos: GridCoords,
+    grid: impl Occupancy,
+    directions: &[IVec2],
+) -> HashSet<GridCoords> {
     let mut moves = HashSet::default();
 
     for dir in directions {
@@ -281,7 +688,7 @@ fn sliding_moves(pos: GridCoords, grid: ChessGrid, directions: &[IVec2]) -> Hash
 
         while GridCoords(current).in_bounds() {
             let potential = GridCoords(current);
-            if grid.get_piece(potential).is_some() {
+            if grid.is_occupied(potential) {
                 break;
             }
             moves.insert(potential);
@@ -292,7 +699,11 @@ fn sliding_moves(pos: GridCoords, grid: ChessGrid, directions: &[IVec2]) -> Hash
     moves
 }
 
-fn sliding_attacks(pos: GridCoords, grid: ChessGrid, directions: &[IVec2]) -> HashSet<GridCoords> {
+fn sliding_attacks(
+    pos: GridCoords,
+    grid: impl Occupancy,
+    directions: &[IVec2],
+) -> HashSet<GridCoords> {
     let mut attacks = HashSet::default();
 
     for dir in directions {
@@ -302,7 +713,7 @@ fn sliding_attacks(pos: GridCoords, grid: ChessGrid, directions: &[IVec2]) -> Ha
             let potential = GridCoords(current);
             attacks.insert(potential);
 
-            if grid.get_piece(potential).is_some() {
+            if grid.is_occupied(potential) {
                 break;
             }
             current += *dir;
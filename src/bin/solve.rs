@@ -0,0 +1,54 @@
+//! Checks authored puzzles for cooked solutions, printing every winning line.
+//!
+//! ```text
+//! cargo run --release --bin solve -- assets/puzzles/*.puzzle.ron
+//! ```
+//!
+//! Exits with an error if any puzzle can't be read or doesn't have exactly one solution.
+
+use bevy_jam_7::{authored::Puzzle, solver};
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let paths: Vec<String> = std::env::args().skip(1).collect();
+
+    if paths.is_empty() {
+        eprintln!("Usage: solve <puzzle file>...");
+        return ExitCode::FAILURE;
+    }
+
+    let mut sound = true;
+
+    for path in paths {
+        let puzzle = match Puzzle::read(&path) {
+            Ok(puzzle) => puzzle,
+            Err(error) => {
+                eprintln!("Could not read {path}: {error}");
+                sound = false;
+                continue;
+            }
+        };
+
+        let lines = solver::solve(&puzzle);
+
+        let first_turns = solver::first_turns(&lines);
+
+        match first_turns {
+            0 => println!("{:?} has no forced solution", puzzle.name),
+            1 => println!("{:?} has a unique solution", puzzle.name),
+            n => println!("{:?} is cooked: {n} winning first turns", puzzle.name),
+        }
+
+        sound &= first_turns == 1;
+
+        for line in &lines {
+            println!("    {}", solver::format_line(line));
+        }
+    }
+
+    if sound {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
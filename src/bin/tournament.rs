@@ -0,0 +1,360 @@
+//! Headless round-robin tournaments between computer players, for ranking AI strategies.
+//!
+//! ```text
+//! cargo run --bin tournament -- \
+//!     --players random,aggressive,passive,bot:target/debug/examples/sample_bot --games 4 --seed 7
+//! ```
+//!
+//! Every pair of players meets on `--games` seeded start positions, playing each one twice so
+//! both get to be white. Games are played on [`Board`]s alone, with the same moves and attack
+//! phases as the game, and end when a king falls or after `--max-passes` passes as a draw.
+
+use bevy_jam_7::{ai::Opponent, behaviour::*, bot::BotProcess, stats::TURNS_PER_PASS};
+use rand::{prelude::*, rngs::StdRng};
+use std::process::ExitCode;
+
+const DEFAULT_GAMES: u32 = 2;
+const DEFAULT_MAX_PASSES: u32 = 100;
+const START_ELO: f32 = 1500.0;
+const ELO_K: f32 = 24.0;
+
+struct Config {
+    players: Vec<String>,
+    games: u32,
+    seed: u64,
+    max_passes: u32,
+}
+
+impl Config {
+    /// The tournament asked for on the command line, if it names its players.
+    fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        let value = |flag: &str| {
+            args.iter()
+                .position(|arg| arg == flag)
+                .and_then(|index| args.get(index + 1))
+        };
+
+        Some(Self {
+            players: value("--players")?.split(',').map(str::to_string).collect(),
+            games: value("--games")
+                .and_then(|games| games.parse().ok())
+                .unwrap_or(DEFAULT_GAMES),
+            seed: value("--seed")
+                .and_then(|seed| seed.parse().ok())
+                .unwrap_or_default(),
+            max_passes: value("--max-passes")
+                .and_then(|passes| passes.parse().ok())
+                .unwrap_or(DEFAULT_MAX_PASSES),
+        })
+    }
+}
+
+/// A built-in [`Opponent`] or an external bot, given as `bot:<command>`.
+enum Player {
+    Ai(Opponent),
+    Bot(BotProcess),
+}
+
+impl Player {
+    fn new(name: &str) -> Result<Self, String> {
+        if let Some(command) = name.strip_prefix("bot:") {
+            return BotProcess::spawn(command)
+                .map(Player::Bot)
+                .map_err(|error| format!("could not start bot {command}: {error}"));
+        }
+
+        Opponent::from_name(name)
+            .map(Player::Ai)
+            .ok_or_else(|| format!("unknown player {name}"))
+    }
+
+    fn play_turn(&self, board: &mut Board, color: PieceColor, rng: &mut impl Rng) {
+        match self {
+            Player::Ai(opponent) => {
+                for _ in 0..TURNS_PER_PASS {
+                    let Some((from, to)) = opponent.choose_move(board, color, rng) else {
+                        break;
+                    };
+                    board.move_piece(from, to);
+                }
+            }
+            Player::Bot(bot) => {
+                for (from, to) in bot.request_moves(board, color, TURNS_PER_PASS) {
+                    board.move_piece(from, to);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Standing {
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    elo: f32,
+}
+
+impl Standing {
+    fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    fn score(&self) -> f32 {
+        self.wins as f32 + self.draws as f32 / 2.0
+    }
+}
+
+/// The standard layout with both back ranks shuffled the same way, so the sides stay mirrored.
+fn start_position(rng: &mut impl Rng) -> Board {
+    let mut board = Board::standard();
+
+    let mut files: Vec<usize> = (0..8).collect();
+    files.shuffle(rng);
+
+    let original = board;
+    for (to, from) in files.into_iter().enumerate() {
+        board.0[to][0] = original.0[from][0];
+        board.0[to][7] = original.0[from][7];
+    }
+
+    board
+}
+
+fn king_alive(board: &Board, color: PieceColor) -> bool {
+    board
+        .pieces()
+        .any(|(_, piece)| piece.color == color && piece.kind == PieceKind::King)
+}
+
+#[derive(Clone, Copy)]
+enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl GameResult {
+    fn white_score(self) -> f32 {
+        match self {
+            GameResult::WhiteWins => 1.0,
+            GameResult::BlackWins => 0.0,
+            GameResult::Draw => 0.5,
+        }
+    }
+}
+
+fn play_game(
+    white: &Player,
+    black: &Player,
+    board: Board,
+    max_passes: u32,
+    rng: &mut impl Rng,
+) -> GameResult {
+    let mut board = board;
+
+    for _ in 0..max_passes {
+        for (player, color) in [(white, PieceColor::White), (black, PieceColor::Black)] {
+            player.play_turn(&mut board, color, rng);
+            board.apply_damage_with_xp(color);
+
+            match (
+                king_alive(&board, PieceColor::White),
+                king_alive(&board, PieceColor::Black),
+            ) {
+                (true, true) => {}
+                (true, false) => return GameResult::WhiteWins,
+                (false, true) => return GameResult::BlackWins,
+                (false, false) => return GameResult::Draw,
+            }
+        }
+    }
+
+    GameResult::Draw
+}
+
+/// Moves both ratings towards the result of one game between them.
+fn update_elo(standings: &mut [Standing], white: usize, black: usize, score: f32) {
+    let expected = 1.0 / (1.0 + 10f32.powf((standings[black].elo - standings[white].elo) / 400.0));
+    let change = ELO_K * (score - expected);
+
+    standings[white].elo += change;
+    standings[black].elo -= change;
+}
+
+/// Indices into `standings` from the highest rated player down, keeping ties in entry order.
+fn ranking(standings: &[Standing]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..standings.len()).collect();
+    order.sort_by(|a, b| standings[*b].elo.total_cmp(&standings[*a].elo));
+    order
+}
+
+/// Runs the tournament and prints the standings.
+fn main() -> ExitCode {
+    let Some(config) = Config::from_args() else {
+        eprintln!(
+            "Usage: tournament --players <player>,<player>,... [--games <n>] [--seed <n>] \
+             [--max-passes <n>]"
+        );
+        return ExitCode::FAILURE;
+    };
+
+    run(config)
+}
+
+fn run(config: Config) -> ExitCode {
+    let players: Vec<Player> = match config
+        .players
+        .iter()
+        .map(|name| Player::new(name))
+        .collect()
+    {
+        Ok(players) => players,
+        Err(error) => {
+            eprintln!("Tournament not started: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut standings: Vec<Standing> = players
+        .iter()
+        .map(|_| Standing {
+            elo: START_ELO,
+            ..Standing::default()
+        })
+        .collect();
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let positions: Vec<Board> = (0..config.games)
+        .map(|_| start_position(&mut rng))
+        .collect();
+
+    for a in 0..players.len() {
+        for b in a + 1..players.len() {
+            for (index, board) in positions.iter().enumerate() {
+                for (white, black) in [(a, b), (b, a)] {
+                    // Each game gets its own seed, so results don't depend on the pairing order.
+                    let mut game_rng = StdRng::seed_from_u64(config.seed ^ ((index as u64) << 32));
+                    let result = play_game(
+                        &players[white],
+                        &players[black],
+                        *board,
+                        config.max_passes,
+                        &mut game_rng,
+                    );
+
+                    match result {
+                        GameResult::WhiteWins => {
+                            standings[white].wins += 1;
+                            standings[black].losses += 1;
+                        }
+                        GameResult::BlackWins => {
+                            standings[white].losses += 1;
+                            standings[black].wins += 1;
+                        }
+                        GameResult::Draw => {
+                            standings[white].draws += 1;
+                            standings[black].draws += 1;
+                        }
+                    }
+
+                    update_elo(&mut standings, white, black, result.white_score());
+                    println!(
+                        "{} vs {} on position {}: {}",
+                        config.players[white],
+                        config.players[black],
+                        index + 1,
+                        match result {
+                            GameResult::WhiteWins => "1-0",
+                            GameResult::BlackWins => "0-1",
+                            GameResult::Draw => "draw",
+                        }
+                    );
+                }
+            }
+        }
+    }
+
+    let width = config
+        .players
+        .iter()
+        .map(String::len)
+        .max()
+        .unwrap_or(0)
+        .max(6);
+
+    println!();
+    println!(
+        "{:>4}  {:<width$}  {:>5}  {:>4}  {:>4}  {:>4}  {:>5}  {:>4}",
+        "Rank", "Player", "Games", "Won", "Drew", "Lost", "Score", "Elo"
+    );
+
+    for (rank, index) in ranking(&standings).into_iter().enumerate() {
+        let standing = &standings[index];
+
+        println!(
+            "{:>4}  {:<width$}  {:>5}  {:>4}  {:>4}  {:>4}  {:>5.1}  {:>4.0}",
+            rank + 1,
+            config.players[index],
+            standing.games(),
+            standing.wins,
+            standing.draws,
+            standing.losses,
+            standing.score(),
+            standing.elo
+        );
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rated(elos: &[f32]) -> Vec<Standing> {
+        elos.iter()
+            .map(|elo| Standing {
+                elo: *elo,
+                ..Standing::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn elo_changes_are_symmetric() {
+        let mut standings = rated(&[START_ELO, START_ELO]);
+
+        update_elo(&mut standings, 0, 1, 1.0);
+
+        assert_eq!(standings[0].elo, START_ELO + ELO_K / 2.0);
+        assert_eq!(standings[1].elo, START_ELO - ELO_K / 2.0);
+        assert_eq!(standings[0].elo + standings[1].elo, 2.0 * START_ELO);
+    }
+
+    #[test]
+    fn elo_follows_the_expected_score() {
+        // 400 points apart, the stronger player is expected to score 10/11.
+        let mut standings = rated(&[1900.0, 1500.0]);
+
+        update_elo(&mut standings, 0, 1, 0.5);
+
+        let change = ELO_K * (0.5 - 10.0 / 11.0);
+        assert!((standings[0].elo - (1900.0 + change)).abs() < 1e-3);
+        assert!((standings[1].elo - (1500.0 - change)).abs() < 1e-3);
+
+        // A draw between equals changes nothing.
+        let mut standings = rated(&[START_ELO, START_ELO]);
+        update_elo(&mut standings, 1, 0, 0.5);
+        assert_eq!(standings[0].elo, START_ELO);
+        assert_eq!(standings[1].elo, START_ELO);
+    }
+
+    #[test]
+    fn standings_are_ranked_by_elo() {
+        let standings = rated(&[1490.0, 1530.0, 1500.0, 1530.0]);
+
+        assert_eq!(ranking(&standings), [1, 3, 2, 0]);
+    }
+}
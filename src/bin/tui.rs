@@ -0,0 +1,366 @@
+//! Terminal frontend, for playing or debugging where no window is available, e.g. over SSH.
+//!
+//! ```text
+//! cargo run --bin tui -- --opponent aggressive
+//! ```
+//!
+//! The board is drawn with ANSI colors: white pieces in capitals, black in lowercase, each with its
+//! health. Commands are read a line at a time: a square such as `e2` selects it like a click does,
+//! a second square moves the selected piece there, `e2e3` does both at once, `p` passes, `esc` (or
+//! the escape key followed by enter) deselects and `q` quits.
+
+use bevy::platform::collections::HashSet;
+use bevy_jam_7::{ai::Opponent, behaviour::*, bot::parse_move, stats::TURNS_PER_PASS};
+use std::{
+    io::{self, BufRead, Write},
+    process::ExitCode,
+};
+
+const CLEAR: &str = "\x1b[2J\x1b[H";
+const RESET: &str = "\x1b[0m";
+const DARK: &str = "\x1b[44m";
+const LIGHT: &str = "\x1b[104m";
+const SELECT: &str = "\x1b[41m";
+const LEGAL: &str = "\x1b[42m";
+const ATTACK: &str = "\x1b[43m";
+const WHITE_PIECE: &str = "\x1b[1;97m";
+const BLACK_PIECE: &str = "\x1b[1;30m";
+
+struct Tui {
+    board: Board,
+    selected: Option<GridCoords>,
+    turns: u8,
+    opponent: Opponent,
+    message: String,
+    winner: Option<PieceColor>,
+}
+
+impl Tui {
+    fn new(board: Board, opponent: Opponent) -> Self {
+        Self {
+            board,
+            selected: None,
+            turns: TURNS_PER_PASS,
+            opponent,
+            message: String::new(),
+            winner: None,
+        }
+    }
+
+    /// Squares the selected piece may move to, only for white pieces with moves left.
+    fn legal_squares(&self) -> HashSet<GridCoords> {
+        match self
+            .selected
+            .and_then(|pos| Some((pos, self.board.get(pos)?)))
+        {
+            Some((pos, piece)) if piece.color == PieceColor::White && self.turns > 0 => {
+                legal_moves(piece, pos, self.board)
+            }
+            _ => HashSet::default(),
+        }
+    }
+
+    /// Enemy pieces the selected piece would hit when its side passes.
+    fn attacked_squares(&self) -> HashSet<GridCoords> {
+        let Some((pos, piece)) = self
+            .selected
+            .and_then(|pos| Some((pos, self.board.get(pos)?)))
+        else {
+            return HashSet::default();
+        };
+
+        attacks(piece, pos, self.board)
+            .into_iter()
+            .filter(|target| {
+                self.board
+                    .get(*target)
+                    .is_some_and(|target| target.color != piece.color)
+            })
+            .collect()
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let legal = self.legal_squares();
+        let attacked = self.attacked_squares();
+
+        write!(out, "{CLEAR}")?;
+        write!(out, "   ")?;
+        for file in 'a'..='h' {
+            write!(out, "  {file}   ")?;
+        }
+        writeln!(out)?;
+
+        for y in 0..8 {
+            write!(out, "{}  ", 8 - y)?;
+
+            for x in 0..8 {
+                let coords = GridCoords::new(x, y);
+
+                let background = if self.selected == Some(coords) {
+                    SELECT
+                } else if legal.contains(&coords) {
+                    LEGAL
+                } else if attacked.contains(&coords) {
+                    ATTACK
+                } else if (x + y) % 2 == 0 {
+                    LIGHT
+                } else {
+                    DARK
+                };
+
+                match self.board.get(coords) {
+                    Some(piece) => {
+                        let (foreground, letter) = match piece.color {
+                            PieceColor::White => (WHITE_PIECE, piece.kind.letter()),
+                            PieceColor::Black => {
+                                (BLACK_PIECE, piece.kind.letter().to_ascii_lowercase())
+                            }
+                        };
+
+                        write!(
+                            out,
+                            "{background}{foreground} {letter}{:>3} {RESET}",
+                            piece.health.ceil()
+                        )?;
+                    }
+                    None => write!(out, "{background}      {RESET}")?,
+                }
+            }
+
+            writeln!(out, "  {}", 8 - y)?;
+        }
+
+        writeln!(out)?;
+
+        if let Some(piece) = self.selected.and_then(|pos| self.board.get(pos)) {
+            writeln!(
+                out,
+                "Selected: {:?} ({:?})  Health: {}/{}  Damage: {}  Level: {}  XP: {}",
+                piece.kind,
+                piece.color,
+                piece.health.ceil(),
+                piece.max_health(),
+                piece.damage,
+                piece.level,
+                piece.xp
+            )?;
+        }
+
+        match self.winner {
+            Some(color) => writeln!(out, "{color:?} wins! Press [Q] to quit")?,
+            None => writeln!(out, "Turns left: {}", self.turns)?,
+        }
+
+        if !self.message.is_empty() {
+            writeln!(out, "{}", self.message)?;
+        }
+
+        writeln!(
+            out,
+            "Type a square to select, a second one to move, [P] to pass, [ESC] to deselect, [Q] to quit"
+        )?;
+        write!(out, "> ")?;
+        out.flush()
+    }
+
+    /// Handles one line of input, returning `false` once the player quits.
+    fn handle(&mut self, input: &str) -> bool {
+        self.message.clear();
+        let input = input.trim().to_lowercase();
+
+        match input.as_str() {
+            "q" | "quit" => return false,
+            "\x1b" | "esc" => self.selected = None,
+            _ if self.winner.is_some() => {}
+            "p" => self.pass(),
+            _ => {
+                if let Some((from, to)) = parse_move(&input) {
+                    self.selected = Some(from);
+                    self.move_selected(to);
+                } else if let Ok(coords) = input.parse::<GridCoords>() {
+                    if self.legal_squares().contains(&coords) {
+                        self.move_selected(coords);
+                    } else {
+                        self.selected = Some(coords);
+                    }
+                } else if !input.is_empty() {
+                    self.message = format!("Unknown command: {input}");
+                }
+            }
+        }
+
+        true
+    }
+
+    fn move_selected(&mut self, to: GridCoords) {
+        if !self.legal_squares().contains(&to) {
+            self.message = "Illegal move".to_string();
+            return;
+        }
+
+        if let Some(from) = self.selected.take() {
+            self.board.move_piece(from, to);
+            self.turns -= 1;
+        }
+    }
+
+    /// White's attack phase, then black's moves and attack phase, as in the game.
+    fn pass(&mut self) {
+        self.selected = None;
+
+        self.board.apply_damage_with_xp(PieceColor::White);
+        if self.check_kings() {
+            return;
+        }
+
+        let mut rng = rand::rng();
+
+        for _ in 0..TURNS_PER_PASS {
+            let Some((from, to)) =
+                self.opponent
+                    .choose_move(&self.board, PieceColor::Black, &mut rng)
+            else {
+                break;
+            };
+
+            self.board.move_piece(from, to);
+        }
+
+        self.board.apply_damage_with_xp(PieceColor::Black);
+        self.check_kings();
+        self.turns = TURNS_PER_PASS;
+    }
+
+    /// Ends the game once a king has fallen, returning whether it has.
+    fn check_kings(&mut self) -> bool {
+        let kings: Vec<PieceColor> = self
+            .board
+            .pieces()
+            .filter(|(_, piece)| piece.kind == PieceKind::King)
+            .map(|(_, piece)| piece.color)
+            .collect();
+
+        if let [color] = kings[..] {
+            self.winner = Some(color);
+        }
+
+        self.winner.is_some()
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let opponent = match args.iter().position(|arg| arg == "--opponent") {
+        None => Opponent::default(),
+        Some(index) => match args
+            .get(index + 1)
+            .and_then(|name| Opponent::from_name(name))
+        {
+            Some(opponent) => opponent,
+            None => {
+                eprintln!("--opponent must be one of: {}", Opponent::NAMES.join(", "));
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+
+    match run(opponent) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Terminal frontend failed: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Plays a game against the computer in the terminal until the player quits.
+fn run(opponent: Opponent) -> io::Result<()> {
+    let mut tui = Tui::new(Board::standard(), opponent);
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+
+    tui.draw(&mut stdout)?;
+
+    for line in stdin.lock().lines() {
+        if !tui.handle(&line?) {
+            break;
+        }
+
+        tui.draw(&mut stdout)?;
+    }
+
+    writeln!(stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(name: &str) -> GridCoords {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn selecting_then_moving_spends_a_turn() {
+        let mut tui = Tui::new(Board::standard(), Opponent::Passive);
+
+        assert!(tui.handle("e2"));
+        assert_eq!(tui.selected, Some(square("e2")));
+
+        assert!(tui.handle("e3"));
+        assert_eq!(tui.selected, None);
+        assert_eq!(tui.board.get(square("e2")), None);
+        assert_eq!(
+            tui.board.get(square("e3")).map(|piece| piece.kind),
+            Some(PieceKind::Pawn)
+        );
+        assert_eq!(tui.turns, TURNS_PER_PASS - 1);
+    }
+
+    #[test]
+    fn illegal_moves_are_refused() {
+        let mut tui = Tui::new(Board::standard(), Opponent::Passive);
+
+        tui.handle("e2e6");
+
+        assert_eq!(tui.message, "Illegal move");
+        assert_eq!(tui.board, Board::standard());
+        assert_eq!(tui.turns, TURNS_PER_PASS);
+    }
+
+    #[test]
+    fn passing_gives_back_every_turn() {
+        let mut tui = Tui::new(Board::standard(), Opponent::Passive);
+
+        tui.handle("e2e3");
+        tui.handle("p");
+
+        assert_eq!(tui.turns, TURNS_PER_PASS);
+        assert_eq!(tui.winner, None);
+    }
+
+    #[test]
+    fn killing_the_king_wins() {
+        let mut board = Board::default();
+        let mut king = Piece::new(PieceColor::Black, PieceKind::King);
+        king.health = 1.0;
+        board.set(square("e8"), Some(king));
+        board.set(
+            square("e1"),
+            Some(Piece::new(PieceColor::White, PieceKind::King)),
+        );
+        board.set(
+            square("e4"),
+            Some(Piece::new(PieceColor::White, PieceKind::Rook)),
+        );
+        let mut tui = Tui::new(board, Opponent::Passive);
+
+        tui.handle("p");
+
+        assert_eq!(tui.winner, Some(PieceColor::White));
+        assert!(tui.handle("e1e2"));
+        assert_eq!(tui.board.get(square("e2")), None);
+        assert!(!tui.handle("q"));
+    }
+}
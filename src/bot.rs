@@ -0,0 +1,324 @@
+//! External bots. Either side can be handed to a separate program that talks a small line-based
+//! protocol over its stdin and stdout, in the spirit of UCI:
+//!
+//! ```text
+//! game: fdc 1                               protocol version
+//! bot:  id name Sample Bot                  optional, any number of `id` lines
+//! bot:  ready
+//! game: position black 3 wKe1:300:10 bPe7:50:10 ...
+//! game: go
+//! bot:  moves e7e6 g8f6                     up to the remaining actions, empty to pass
+//! game: quit
+//! ```
+//!
+//! A position lists the side to move, its remaining actions, then every piece as color, letter,
+//! square, health and damage. Lines the game doesn't expect, such as `info ...`, are ignored.
+//! Moves are checked against the rules in order and the first illegal one ends the turn.
+//!
+//! The game drives bots through [`BotProcess`], and so does the tournament runner.
+//! `examples/sample_bot.rs` is the reference implementation.
+
+use crate::behaviour::{Move, *};
+use bevy::prelude::*;
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        Mutex,
+        mpsc::{self, Receiver},
+    },
+    thread,
+    time::Duration,
+};
+
+/// Bumped whenever the text protocol changes, so bots can refuse versions they don't know.
+pub const PROTOCOL_VERSION: u32 = 1;
+/// How long a bot may think before its turn is passed for it.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A running bot program.
+pub struct BotProcess {
+    pub name: String,
+    child: Child,
+    stdin: Mutex<ChildStdin>,
+    lines: Mutex<Receiver<String>>,
+}
+
+impl BotProcess {
+    /// Starts `command`, split on whitespace into the program and its arguments, and waits for
+    /// it to answer the handshake.
+    pub fn spawn(command: &str) -> io::Result<Self> {
+        let mut parts = command.split_whitespace();
+        let program = parts
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty bot command"))?;
+
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        // Reading happens on its own thread so a silent bot can be timed out.
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut bot = Self {
+            name: program.to_string(),
+            child,
+            stdin: Mutex::new(stdin),
+            lines: Mutex::new(receiver),
+        };
+
+        bot.send(&format!("fdc {PROTOCOL_VERSION}"))?;
+
+        loop {
+            let line = bot.receive()?;
+            let line = line.trim();
+
+            if line == "ready" {
+                break;
+            }
+
+            if let Some(name) = line.strip_prefix("id name ") {
+                bot.name = name.to_string();
+            }
+        }
+
+        Ok(bot)
+    }
+
+    fn send(&self, line: &str) -> io::Result<()> {
+        let mut stdin = self.stdin.lock().unwrap();
+        writeln!(stdin, "{line}")?;
+        stdin.flush()
+    }
+
+    fn receive(&self) -> io::Result<String> {
+        self.lines
+            .lock()
+            .unwrap()
+            .recv_timeout(REPLY_TIMEOUT)
+            .map_err(|error| io::Error::new(io::ErrorKind::TimedOut, error))
+    }
+
+    /// Asks for up to `actions` moves for `color`. Only the legal prefix of the reply is returned,
+    /// and a bot that fails to answer passes.
+    pub fn request_moves(&self, board: &Board, color: PieceColor, actions: u8) -> Vec<Move> {
+        match self.try_request_moves(board, color, actions) {
+            Ok(moves) => moves,
+            Err(error) => {
+                warn!("Bot {} failed to move: {error}", self.name);
+                Vec::new()
+            }
+        }
+    }
+
+    fn try_request_moves(
+        &self,
+        board: &Board,
+        color: PieceColor,
+        actions: u8,
+    ) -> io::Result<Vec<Move>> {
+        self.send(&position_line(board, color, actions))?;
+        self.send("go")?;
+
+        let reply = loop {
+            let line = self.receive()?;
+
+            if let Some(moves) = line.trim().strip_prefix("moves") {
+                break moves.to_string();
+            }
+        };
+
+        let mut board = *board;
+        let mut moves = Vec::new();
+
+        for token in reply.split_whitespace().take(actions as usize) {
+            let Some((from, to)) = parse_move(token) else {
+                warn!("Bot {} sent an unreadable move: {token}", self.name);
+                break;
+            };
+
+            let legal = board.get(from).is_some_and(|piece| {
+                piece.color == color && legal_moves(piece, from, board).contains(&to)
+            });
+
+            if !legal {
+                warn!("Bot {} sent an illegal move: {token}", self.name);
+                break;
+            }
+
+            board.move_piece(from, to);
+            moves.push((from, to));
+        }
+
+        Ok(moves)
+    }
+}
+
+impl Drop for BotProcess {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.child.wait();
+    }
+}
+
+/// The `position` line for `board` with `color` to move and `actions` left.
+pub fn position_line(board: &Board, color: PieceColor, actions: u8) -> String {
+    let mut line = format!("position {} {actions}", color_name(color));
+
+    for x in 0..8 {
+        for y in 0..8 {
+            let coords = GridCoords::new(x, y);
+
+            if let Some(piece) = board.get(coords) {
+                let color = match piece.color {
+                    PieceColor::White => 'w',
+                    PieceColor::Black => 'b',
+                };
+
+                line += &format!(
+                    " {color}{}{coords}:{}:{}",
+                    piece.kind.letter(),
+                    piece.health,
+                    piece.damage
+                );
+            }
+        }
+    }
+
+    line
+}
+
+/// Reads a move like `e2e3`.
+pub fn parse_move(token: &str) -> Option<Move> {
+    let from = token.get(0..2)?.parse().ok()?;
+    let to = token.get(2..4)?.parse().ok()?;
+
+    (token.len() == 4).then_some((from, to))
+}
+
+fn color_name(color: PieceColor) -> &'static str {
+    match color {
+        PieceColor::White => "white",
+        PieceColor::Black => "black",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn square(name: &str) -> GridCoords {
+        name.parse().unwrap()
+    }
+
+    fn piece(color: PieceColor, kind: PieceKind, health: f32, damage: f32) -> Option<Piece> {
+        Some(Piece {
+            health,
+            damage,
+            ..Piece::new(color, kind)
+        })
+    }
+
+    #[test]
+    fn parses_moves() {
+        assert_eq!(parse_move("e2e3"), Some((square("e2"), square("e3"))));
+        assert_eq!(
+            parse_move("a8h1"),
+            Some((GridCoords::new(0, 0), GridCoords::new(7, 7)))
+        );
+
+        for token in ["", "e2", "e2e", "e2e3q", "e2-e3", "e2e9", "i2e3"] {
+            assert_eq!(parse_move(token), None, "{token:?}");
+        }
+    }
+
+    #[test]
+    fn encodes_positions() {
+        let mut board = Board::default();
+        board.set(
+            square("e1"),
+            piece(PieceColor::White, PieceKind::King, 25.0, 10.0),
+        );
+        board.set(
+            square("e7"),
+            piece(PieceColor::Black, PieceKind::Pawn, 7.5, 20.0),
+        );
+        board.set(
+            square("b8"),
+            piece(PieceColor::Black, PieceKind::Knight, 60.0, 10.0),
+        );
+
+        // Pieces are listed file by file, from black's back rank down.
+        assert_eq!(
+            position_line(&board, PieceColor::Black, 2),
+            "position black 2 bNb8:60:10 bPe7:7.5:20 wKe1:25:10"
+        );
+        assert_eq!(
+            position_line(&Board::default(), PieceColor::White, 3),
+            "position white 3"
+        );
+    }
+
+    /// `cargo test` builds the examples next to the test binary's `deps` folder.
+    fn sample_bot() -> String {
+        let exe = env::current_exe().unwrap();
+        let profile_dir = exe.parent().and_then(|deps| deps.parent()).unwrap();
+
+        profile_dir
+            .join("examples")
+            .join(format!("sample_bot{}", env::consts::EXE_SUFFIX))
+            .display()
+            .to_string()
+    }
+
+    #[test]
+    fn sample_bot_plays_legal_moves() {
+        let bot = BotProcess::spawn(&sample_bot()).unwrap();
+        assert_eq!(bot.name, "Sample Bot");
+
+        let mut board = Board::standard();
+
+        for color in [PieceColor::White, PieceColor::Black] {
+            // `request_moves` keeps only the legal part of a reply, so the raw reply is checked here.
+            bot.send(&position_line(&board, color, 3)).unwrap();
+            bot.send("go").unwrap();
+
+            let reply = bot.receive().unwrap();
+            let tokens: Vec<&str> = reply
+                .strip_prefix("moves")
+                .unwrap()
+                .split_whitespace()
+                .collect();
+            assert_eq!(tokens.len(), 3, "{reply}");
+
+            for token in tokens {
+                let (from, to) = parse_move(token).unwrap();
+                let piece = board.get(from).unwrap();
+
+                assert_eq!(piece.color, color, "{token}");
+                assert!(legal_moves(piece, from, board).contains(&to), "{token}");
+                board.move_piece(from, to);
+            }
+        }
+
+        let moves = bot.request_moves(&board, PieceColor::White, 2);
+        assert_eq!(moves.len(), 2);
+    }
+}
@@ -0,0 +1,600 @@
+//! Campaign mode. A [`Campaign`] is a sequence of scripted [`Level`]s, each with its own layout,
+//! opponent and goal, framed by dialogue from the queen. Beating a level unlocks the next one, and
+//! progress is saved between sessions.
+
+use crate::{
+    AppState, BUTTON, BUTTON_HOVER, BUTTON_LOCKED, BoardState, GameMode, Typewriter,
+    actions::{Action, Actions, Nav, PromptText},
+    ai::Opponent,
+    assets::{CampaignCollection, FontsCollection},
+    authored::{Goal, Outcome, PlacedPiece, board_from},
+    behaviour::*,
+    chessboard::{QueenBubbleText, StartingLayout},
+    goal::GoalProgress,
+    save,
+    title::{TitleMode, mode_chosen},
+};
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+const SAVE_FILE: &str = "campaign.ron";
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Campaign>();
+    app.init_asset::<Level>();
+    app.init_asset_loader::<CampaignLoader>();
+    app.init_asset_loader::<LevelLoader>();
+    app.insert_resource(save::load::<CampaignProgress>(SAVE_FILE).unwrap_or_default());
+
+    app.add_systems(
+        Update,
+        open_level_select.run_if(in_state(AppState::Title).and(mode_chosen(TitleMode::Campaign))),
+    );
+    app.add_systems(OnEnter(AppState::LevelSelect), spawn_level_select);
+    app.add_systems(
+        Update,
+        (
+            focus_level,
+            color_level_buttons,
+            click_level_button,
+            leave_level_select,
+        )
+            .chain()
+            .run_if(in_state(AppState::LevelSelect)),
+    );
+
+    app.add_systems(OnEnter(AppState::Main), setup.run_if(in_campaign_mode));
+    app.add_systems(PostUpdate, queue_intro.run_if(in_campaign_mode));
+    app.add_systems(OnExit(AppState::Main), cleanup);
+    app.add_systems(
+        Update,
+        (
+            start_outro.run_if(resource_changed::<GoalProgress>),
+            show_dialogue_line.run_if(resource_changed::<Dialogue>),
+            advance_dialogue.run_if(in_state(BoardState::Dialogue)),
+            update_status_text,
+            campaign_controls,
+        )
+            .chain()
+            .run_if(
+                in_state(AppState::Main)
+                    .and(in_campaign_mode)
+                    .and(resource_exists::<GoalProgress>),
+            ),
+    );
+}
+
+#[derive(Asset, TypePath, Debug)]
+pub struct Campaign {
+    #[dependency]
+    pub levels: Vec<Handle<Level>>,
+}
+
+#[derive(Asset, TypePath, Deserialize, Debug, Clone)]
+pub struct Level {
+    pub name: String,
+    /// Lines said by the queen before the level starts.
+    #[serde(default)]
+    pub intro: Vec<String>,
+    /// Lines said by the queen once the level is won.
+    #[serde(default)]
+    pub outro: Vec<String>,
+    #[serde(default)]
+    pub opponent: Opponent,
+    pub goal: Goal,
+    pub pieces: Vec<PlacedPiece>,
+}
+
+impl Level {
+    pub fn board(&self) -> Board {
+        board_from(&self.name, &self.pieces)
+    }
+}
+
+#[derive(Deserialize)]
+struct CampaignFile {
+    levels: Vec<String>,
+}
+
+#[derive(Default, TypePath)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+#[derive(Default, TypePath)]
+struct CampaignLoader;
+
+impl AssetLoader for CampaignLoader {
+    type Asset = Campaign;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: CampaignFile = ron::de::from_bytes(&bytes)?;
+
+        Ok(Campaign {
+            levels: file
+                .levels
+                .into_iter()
+                .map(|path| load_context.load(path))
+                .collect(),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["campaign.ron"]
+    }
+}
+
+/// How far the player got, saved to disk whenever a level is beaten.
+#[derive(Resource, Serialize, Deserialize, Debug)]
+pub struct CampaignProgress {
+    /// Number of levels that can be played, counting from the first.
+    pub unlocked: usize,
+}
+
+impl Default for CampaignProgress {
+    fn default() -> Self {
+        Self { unlocked: 1 }
+    }
+}
+
+#[derive(Resource)]
+struct Dialogue {
+    lines: Vec<String>,
+    line: usize,
+    then: AfterDialogue,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AfterDialogue {
+    Play,
+    Complete,
+}
+
+/// Index of the level picked with the arrow keys or a pad, starting on the last one unlocked.
+#[derive(Resource)]
+struct LevelFocus(usize);
+
+#[derive(Component)]
+struct LevelButton {
+    index: usize,
+    unlocked: bool,
+}
+
+#[derive(Component)]
+struct CampaignStatusText;
+
+fn in_campaign_mode(mode: Res<GameMode>) -> bool {
+    matches!(*mode, GameMode::Campaign(_))
+}
+
+fn get_level<'a>(
+    index: usize,
+    collection: &CampaignCollection,
+    campaigns: &Assets<Campaign>,
+    levels: &'a Assets<Level>,
+) -> Option<&'a Level> {
+    let campaign = campaigns.get(&collection.campaign)?;
+    levels.get(campaign.levels.get(index)?)
+}
+
+fn open_level_select(mut state: ResMut<NextState<AppState>>) {
+    state.set(AppState::LevelSelect);
+}
+
+fn spawn_level_select(
+    mut commands: Commands,
+    font: Res<FontsCollection>,
+    collection: Res<CampaignCollection>,
+    campaigns: Res<Assets<Campaign>>,
+    levels: Res<Assets<Level>>,
+    progress: Res<CampaignProgress>,
+) {
+    let level_count = campaigns
+        .get(&collection.campaign)
+        .map_or(0, |campaign| campaign.levels.len());
+
+    commands.insert_resource(LevelFocus(
+        progress.unlocked.min(level_count).saturating_sub(1),
+    ));
+
+    commands
+        .spawn((
+            Name::new("Level Select Root"),
+            DespawnOnExit(AppState::LevelSelect),
+            Node {
+                width: percent(100.0),
+                height: percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: px(10.0),
+                ..default()
+            },
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Name::new("Campaign Title"),
+                Text::new("Campaign"),
+                TextFont {
+                    font: font.title.clone(),
+                    font_size: 70.0,
+                    ..default()
+                },
+            ));
+
+            for index in 0..level_count {
+                let unlocked = index < progress.unlocked;
+                let label = match get_level(index, &collection, &campaigns, &levels) {
+                    Some(level) if unlocked => format!("{}. {}", index + 1, level.name),
+                    _ => format!("{}. Locked", index + 1),
+                };
+
+                p.spawn((
+                    Name::new("Level Button"),
+                    Button,
+                    LevelButton { index, unlocked },
+                    Node {
+                        width: px(400.0),
+                        padding: px(10.0).into(),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    BackgroundColor(if unlocked { BUTTON } else { BUTTON_LOCKED }),
+                    children![(
+                        Text::new(label),
+                        TextFont {
+                            font: font.title.clone(),
+                            font_size: 28.0,
+                            ..default()
+                        },
+                        Pickable::IGNORE,
+                    )],
+                ));
+            }
+
+            p.spawn((
+                Name::new("Back Text"),
+                Text::default(),
+                PromptText(|actions| {
+                    format!("Press {} to go back", actions.prompt(Action::Deselect))
+                }),
+                TextFont {
+                    font: font.title.clone(),
+                    font_size: 20.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn focus_level(
+    mut focus: ResMut<LevelFocus>,
+    actions: Actions,
+    buttons: Query<(), With<LevelButton>>,
+) {
+    let count = buttons.iter().count();
+    if count == 0 {
+        return;
+    }
+
+    if actions.nav_just_pressed(Nav::Down) || actions.nav_just_pressed(Nav::Next) {
+        focus.0 = (focus.0 + 1) % count;
+    }
+
+    if actions.nav_just_pressed(Nav::Up) || actions.nav_just_pressed(Nav::Previous) {
+        focus.0 = (focus.0 + count - 1) % count;
+    }
+}
+
+fn color_level_buttons(
+    mut query: Query<(&Interaction, &LevelButton, &mut BackgroundColor)>,
+    focus: Res<LevelFocus>,
+) {
+    for (interaction, button, mut bg) in &mut query {
+        bg.0 = if !button.unlocked {
+            BUTTON_LOCKED
+        } else if *interaction == Interaction::None && focus.0 != button.index {
+            BUTTON
+        } else {
+            BUTTON_HOVER
+        };
+    }
+}
+
+fn click_level_button(
+    mut state: ResMut<NextState<AppState>>,
+    mut mode: ResMut<GameMode>,
+    mut layout: ResMut<StartingLayout>,
+    mut opponent: ResMut<Opponent>,
+    query: Query<(Ref<Interaction>, &LevelButton)>,
+    focus: Res<LevelFocus>,
+    actions: Actions,
+    collection: Res<CampaignCollection>,
+    campaigns: Res<Assets<Campaign>>,
+    levels: Res<Assets<Level>>,
+) {
+    let confirmed = actions.just_pressed(Action::Confirm) || actions.nav_just_pressed(Nav::Select);
+
+    for (interaction, button) in &query {
+        // Holding the mouse down only picks the level once.
+        let clicked = interaction.is_changed() && *interaction == Interaction::Pressed;
+        let picked = clicked || confirmed && focus.0 == button.index;
+
+        if !button.unlocked || !picked {
+            continue;
+        }
+
+        let Some(level) = get_level(button.index, &collection, &campaigns, &levels) else {
+            continue;
+        };
+
+        *mode = GameMode::Campaign(button.index);
+        *layout = StartingLayout(level.board());
+        *opponent = level.opponent;
+        state.set(AppState::Main);
+    }
+}
+
+fn leave_level_select(mut state: ResMut<NextState<AppState>>, actions: Actions) {
+    if actions.just_pressed(Action::Deselect) {
+        state.set(AppState::Title);
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    layout: Res<StartingLayout>,
+    font: Res<FontsCollection>,
+    collection: Res<CampaignCollection>,
+    campaigns: Res<Assets<Campaign>>,
+    levels: Res<Assets<Level>>,
+) {
+    let GameMode::Campaign(index) = *mode else {
+        return;
+    };
+
+    let Some(level) = get_level(index, &collection, &campaigns, &levels) else {
+        warn!("level {index} missing from campaign");
+        return;
+    };
+
+    commands.insert_resource(GoalProgress::new(level.goal, &layout.0));
+
+    if !level.intro.is_empty() {
+        commands.insert_resource(Dialogue {
+            lines: level.intro.clone(),
+            line: 0,
+            then: AfterDialogue::Play,
+        });
+    }
+
+    commands.spawn((
+        Name::new("Campaign Panel"),
+        DespawnOnExit(AppState::Main),
+        Node {
+            position_type: PositionType::Absolute,
+            left: px(10.0),
+            bottom: px(10.0),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Name::new("Campaign Status Text"),
+            Text::default(),
+            CampaignStatusText,
+            TextFont {
+                font: font.title.clone(),
+                font_size: 28.0,
+                ..default()
+            },
+        )],
+    ));
+}
+
+/// Opens a level on its intro, if it has one. Runs once the frame's systems have asked to enter
+/// the level, from the level select, a retry or the pause menu, so the board state is set along
+/// with it and the board never takes input before the queen has spoken.
+fn queue_intro(
+    mut board_state: ResMut<NextState<BoardState>>,
+    state: Res<NextState<AppState>>,
+    mode: Res<GameMode>,
+    collection: Res<CampaignCollection>,
+    campaigns: Res<Assets<Campaign>>,
+    levels: Res<Assets<Level>>,
+) {
+    let (NextState::Pending(AppState::Main), GameMode::Campaign(index)) = (&*state, *mode) else {
+        return;
+    };
+
+    let has_intro = get_level(index, &collection, &campaigns, &levels)
+        .is_some_and(|level| !level.intro.is_empty());
+
+    board_state.set(if has_intro {
+        BoardState::Dialogue
+    } else {
+        BoardState::Playing
+    });
+}
+
+fn cleanup(mut commands: Commands) {
+    commands.remove_resource::<Dialogue>();
+}
+
+fn start_outro(
+    mut commands: Commands,
+    mut board_state: ResMut<NextState<BoardState>>,
+    progress: Res<GoalProgress>,
+    mode: Res<GameMode>,
+    collection: Res<CampaignCollection>,
+    campaigns: Res<Assets<Campaign>>,
+    levels: Res<Assets<Level>>,
+) {
+    let GameMode::Campaign(index) = *mode else {
+        return;
+    };
+
+    if progress.outcome != Some(Outcome::Won) {
+        return;
+    }
+
+    let outro = get_level(index, &collection, &campaigns, &levels)
+        .map(|level| level.outro.clone())
+        .unwrap_or_default();
+
+    commands.insert_resource(Dialogue {
+        lines: outro,
+        line: 0,
+        then: AfterDialogue::Complete,
+    });
+    board_state.set(BoardState::Dialogue);
+}
+
+fn show_dialogue_line(
+    mut bubble_query: Query<(&mut Typewriter, &mut Text), With<QueenBubbleText>>,
+    dialogue: Res<Dialogue>,
+    actions: Actions,
+) {
+    let Some(line) = dialogue.lines.get(dialogue.line) else {
+        return;
+    };
+
+    if let Ok((mut typewriter, mut text)) = bubble_query.single_mut() {
+        typewriter.full_text = actions.fill_prompts(line);
+        typewriter.visible_chars = 0;
+        typewriter.timer.reset();
+        text.0.clear();
+    }
+}
+
+fn advance_dialogue(
+    mut commands: Commands,
+    mut state: ResMut<NextState<AppState>>,
+    mut board_state: ResMut<NextState<BoardState>>,
+    mut campaign_progress: ResMut<CampaignProgress>,
+    mut dialogue: ResMut<Dialogue>,
+    mut bubble_query: Query<(&mut Typewriter, &mut Text), With<QueenBubbleText>>,
+    actions: Actions,
+    mode: Res<GameMode>,
+    collection: Res<CampaignCollection>,
+    campaigns: Res<Assets<Campaign>>,
+) {
+    if !actions.just_pressed(Action::Confirm) {
+        return;
+    }
+
+    if let Ok((mut typewriter, mut text)) = bubble_query.single_mut()
+        && typewriter.visible_chars < typewriter.full_text.len()
+    {
+        typewriter.visible_chars = typewriter.full_text.len();
+        text.0 = typewriter.full_text.clone();
+        return;
+    }
+
+    if dialogue.line + 1 < dialogue.lines.len() {
+        dialogue.line += 1;
+        return;
+    }
+
+    match dialogue.then {
+        AfterDialogue::Play => {
+            commands.remove_resource::<Dialogue>();
+            board_state.set(BoardState::Playing);
+        }
+        AfterDialogue::Complete => {
+            if let GameMode::Campaign(index) = *mode {
+                let count = campaigns
+                    .get(&collection.campaign)
+                    .map_or(0, |campaign| campaign.levels.len());
+                campaign_progress.unlocked = campaign_progress.unlocked.max((index + 2).min(count));
+                save::store(SAVE_FILE, &*campaign_progress);
+            }
+
+            state.set(AppState::LevelSelect);
+        }
+    }
+}
+
+fn update_status_text(
+    mut text_query: Query<&mut Text, With<CampaignStatusText>>,
+    progress: Res<GoalProgress>,
+    board_state: Res<State<BoardState>>,
+    mode: Res<GameMode>,
+    actions: Actions,
+) {
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+
+    let GameMode::Campaign(index) = *mode else {
+        return;
+    };
+
+    let status = match (board_state.get(), progress.outcome) {
+        (BoardState::Dialogue, _) => {
+            format!("Press {} to continue", actions.prompt(Action::Confirm))
+        }
+        (_, Some(Outcome::Lost)) => format!(
+            "Defeat!\nPress {} to retry\nor {} for levels",
+            actions.prompt(Action::Retry),
+            actions.prompt(Action::Deselect)
+        ),
+        (_, Some(Outcome::Won)) => "Victory!".to_string(),
+        _ => format!("Passes: {}/{}", progress.passes, progress.goal.limit()),
+    };
+
+    text.0 = format!(
+        "Level {}\n{}\n{}",
+        index + 1,
+        progress.goal.description(),
+        status
+    );
+}
+
+fn campaign_controls(
+    mut state: ResMut<NextState<AppState>>,
+    actions: Actions,
+    progress: Res<GoalProgress>,
+) {
+    if progress.outcome != Some(Outcome::Lost) {
+        return;
+    }
+
+    if actions.just_pressed(Action::Retry) {
+        state.set(AppState::Main);
+    } else if actions.just_pressed(Action::Deselect) {
+        state.set(AppState::LevelSelect);
+    }
+}
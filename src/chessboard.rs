@@ -1,16 +1,38 @@
 use crate::{
-    AppState, Typewriter, assets::*, behaviour::*, generate_character_text, stats::TurnsStat,
+    AppState, BoardState, PauseState, Typewriter,
+    actions::{Action, Actions, Nav, PromptText, action_just_pressed},
+    ai::Opponent,
+    assets::*,
+    behaviour::*,
+    faller::Debris,
+    generate_character_text,
+    settings::Settings,
+    stats::{ActionBudget, TURNS_PER_PASS, TurnsStat},
+};
+use bevy::{
+    color::Mix,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+    window::PrimaryWindow,
 };
-use bevy::{audio::Sample, platform::collections::HashSet, prelude::*};
 use bevy_seedling::sample::{AudioSample, SamplePlayer};
-use rand::prelude::*;
-
-const DARK: Color = Color::hsl(200.0, 1.0, 0.25);
-const LIGHT: Color = Color::hsl(200.0, 1.0, 0.5);
-const HOVER: Color = Color::hsl(200.0, 1.0, 0.8);
-const LEGAL: Color = Color::hsl(100.0, 0.5, 0.8);
-const SELECT: Color = Color::hsl(10.0, 0.5, 0.8);
-const ATTACK: Color = Color::hsl(50.0, 0.9, 0.5);
+use std::{collections::VecDeque, f32::consts::PI};
+
+/// How strongly the theme's attack color shows on the squares the hovered piece attacks.
+const PREVIEW_STRENGTH: f32 = 0.35;
+/// The damage at which a square shows a heatmap at full strength.
+const HEATMAP_FULL_DAMAGE: f32 = 30.0;
+const HEATMAP_STRENGTH: f32 = 0.6;
+/// Tint a piece flashes to when hit, fading back over [`HIT_FLASH_SECS`].
+const HIT_FLASH: Color = Color::hsl(0.0, 1.0, 0.5);
+const HIT_FLASH_SECS: f32 = 0.3;
+/// How long a damage number takes to rise and fade, and how far it rises.
+const DAMAGE_NUMBER_SECS: f32 = 0.9;
+const DAMAGE_NUMBER_RISE: f32 = 40.0;
+/// How high a knight hops, in squares.
+const HOP_HEIGHT: f32 = 0.5;
+/// How long a dead piece's sprites take to fall off-screen.
+const DEBRIS_SECS: f32 = 2.5;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(AppState::Main), setup);
@@ -19,20 +41,113 @@ pub(super) fn plugin(app: &mut App) {
         (
             interact,
             deselect,
+            keyboard_cursor,
             find_legal_moves,
+            preview_hovered_piece,
+            toggle_heatmaps,
+            refresh_heatmaps.run_if(
+                resource_changed::<Heatmaps>
+                    .or(resource_changed::<ChessGrid>)
+                    .or(resource_changed::<ActiveSide>)
+                    .or(on_message::<PieceMoved>)
+                    .or(on_message::<SidePassed>)
+                    .or(on_message::<TurnPassed>),
+            ),
             update_tile_colors,
             update_selected_text,
+            update_level_badges,
+            toggle_health_bars.run_if(action_just_pressed(Action::ToggleHealthBars)),
+            update_health_bars,
+            show_health_bars,
             update_turns_text.run_if(resource_changed::<TurnsStat>),
-            pass_turn.run_if(resource_changed::<ButtonInput<KeyCode>>),
+            pass_turn.run_if(action_just_pressed(Action::Pass)),
         )
             .chain()
+            .run_if(in_state(BoardState::Playing).and(in_state(PauseState::Running))),
+    );
+    app.add_systems(
+        Update,
+        (
+            float_damage_numbers,
+            flash_hits,
+            animate_moves,
+            break_up_dead_pieces,
+            // Recolors the board right away when the theme changes, even with the game paused.
+            (update_tile_colors, recolor_board_frame).run_if(resource_changed::<Settings>),
+        )
+            .run_if(in_state(AppState::Main)),
+    );
+    app.add_systems(
+        Update,
+        lift_dead_pieces
+            .after(resolve_pass)
             .run_if(in_state(AppState::Main)),
     );
-    app.insert_resource(TurnsStat(3));
+    app.add_systems(OnExit(BoardState::Playing), hide_tooltip);
+    app.add_systems(PostUpdate, tint_pieces);
+    app.add_systems(
+        Update,
+        resolve_pass
+            .after(pass_turn)
+            .run_if(in_state(PauseState::Running).and(on_message::<PassRequested>)),
+    );
+    app.add_message::<PassRequested>();
+    app.add_message::<PieceMoved>();
+    app.add_message::<SidePassed>();
+    app.add_message::<TurnPassed>();
+    app.insert_resource(TurnsStat(TURNS_PER_PASS));
+    app.init_resource::<ActionBudget>();
+    app.init_resource::<Heatmaps>();
+    app.init_resource::<HeatmapValues>();
+    app.init_resource::<MoveAnimations>();
+    app.init_resource::<ActiveSide>();
+    app.init_resource::<StartingLayout>();
     app.register_type::<GridCoords>();
     app.register_type::<ChessGrid>();
 }
 
+/// Pieces placed on the board when entering [`AppState::Main`]. Defaults to the standard chess
+/// starting position.
+#[derive(Resource, Clone, Copy)]
+pub struct StartingLayout(pub Board);
+
+impl Default for StartingLayout {
+    fn default() -> Self {
+        Self(Board::standard())
+    }
+}
+
+/// The side currently moving. Always white unless black passes on its own, as a
+/// [`Opponent::Human`], [`Opponent::Remote`] or [`Opponent::External`] does.
+#[derive(Resource)]
+pub struct ActiveSide(pub PieceColor);
+
+impl Default for ActiveSide {
+    fn default() -> Self {
+        Self(PieceColor::White)
+    }
+}
+
+/// Asks for the active side's pass to be resolved, either from the pass key or from elsewhere,
+/// such as a remote player.
+#[derive(Message)]
+pub struct PassRequested;
+
+/// Written when the local player moves a piece.
+#[derive(Message)]
+pub struct PieceMoved {
+    pub from: GridCoords,
+    pub to: GridCoords,
+}
+
+/// Written in two-player modes once one side's attack phase has been applied.
+#[derive(Message)]
+pub struct SidePassed(pub PieceColor);
+
+/// Written at the end of [`resolve_pass`], once both damage phases have been applied.
+#[derive(Message)]
+pub struct TurnPassed;
+
 #[derive(Component)]
 pub struct SelectedText;
 
@@ -48,26 +163,120 @@ pub struct LegalSquare;
 #[derive(Component)]
 pub struct AttackedSquare;
 
+/// A square attacked by the hovered piece.
+#[derive(Component)]
+pub struct PreviewSquare;
+
 #[derive(Component)]
 pub struct TileGrid;
 
+/// Stats of the hovered piece, following the mouse.
+#[derive(Component)]
+struct HoverTooltip;
+
 #[derive(Component)]
 pub struct PieceNode;
 
+/// The background sprite of a piece or of the queen portrait, tinted by its color.
+#[derive(Component)]
+struct PieceNodeBg(PieceColor);
+
+/// The chessboard node, whose background shows as a frame around the squares.
+#[derive(Component)]
+struct BoardFrame;
+
+/// Damage dealt to a square in one attack phase, rising and fading above it.
+#[derive(Component)]
+struct DamageNumber(Timer);
+
+/// The sprites of a piece that just died, lifted off its square so the square is free for play.
+#[derive(Component)]
+struct Dying {
+    coords: GridCoords,
+    sound: Handle<AudioSample>,
+    /// Runs from the impact until the sprites have fallen off-screen.
+    timer: Option<Timer>,
+}
+
+/// Set on a piece that was just hit, while its sprites fade back from [`HIT_FLASH`].
+#[derive(Component)]
+struct HitFlash(Timer);
+
+/// A piece's sprites sliding from the square it left to the one it now stands on.
+#[derive(Clone, Copy)]
+struct Slide {
+    piece: Entity,
+    from: GridCoords,
+    to: GridCoords,
+}
+
+/// Moves waiting to be shown. Only the sprites lag behind; the board is updated straight away.
+#[derive(Resource, Default)]
+struct MoveAnimations {
+    current: Option<(Slide, Timer)>,
+    queue: VecDeque<Slide>,
+}
+
+/// Board overlays tinting each square by the damage it would take in the next attack phase:
+/// `threat` from the other side, toggled by [`Action::ToggleThreats`], and `coverage` from the side
+/// to move, toggled by [`Action::ToggleCoverage`].
+#[derive(Resource, Default)]
+pub struct Heatmaps {
+    pub threat: bool,
+    pub coverage: bool,
+}
+
+/// The damage shown by the [`Heatmaps`] that are switched on, kept until the board changes.
+#[derive(Resource, Default)]
+struct HeatmapValues {
+    threat: Option<[[f32; 8]; 8]>,
+    coverage: Option<[[f32; 8]; 8]>,
+}
+
+/// Either part of a piece's health bar.
+#[derive(Component)]
+struct HealthBar;
+
+/// The part of a health bar that shrinks with the piece's health, tinted by the piece's color.
+#[derive(Component)]
+struct HealthBarFill(PieceColor);
+
+/// Square under the keyboard cursor. Hidden until a cursor key is first pressed.
+#[derive(Resource, Default)]
+pub struct BoardCursor(pub Option<GridCoords>);
+
+/// The piece being dragged, from the start of the drag until it is let go.
+#[derive(Resource)]
+struct Dragging {
+    from: GridCoords,
+    /// Sprites and badges following the cursor.
+    nodes: Vec<Entity>,
+    dropped: bool,
+}
+
 #[derive(Component)]
 pub struct QueenBubbleText;
 
+/// Shows a piece's level on its node, next to the sprites.
+#[derive(Component)]
+pub struct LevelBadge;
+
 fn setup(
     mut commands: Commands,
+    layout: Res<StartingLayout>,
+    budget: Res<ActionBudget>,
     font: Res<FontsCollection>,
     fg: Res<SpritesFgCollection>,
     bg: Res<SpritesBgCollection>,
+    settings: Res<Settings>,
 ) {
     let mut chessgrid = ChessGrid::default();
+    let palette = settings.theme.palette();
 
     commands
         .spawn((
             Name::new("Main Node"),
+            DespawnOnExit(AppState::Main),
             Node {
                 width: percent(100.0),
                 height: percent(100.0),
@@ -91,7 +300,8 @@ fn setup(
                 children![
                     (
                         Name::new("Pass Text"),
-                        Text::new("Press [P] to Pass\nor End Turn"),
+                        Text::default(),
+                        PromptText(pass_text),
                         TextFont {
                             font: font.title.clone(),
                             font_size: 32.0,
@@ -100,7 +310,7 @@ fn setup(
                     ),
                     (
                         Name::new("Turns Text"),
-                        Text::new("Turns Left: 3"),
+                        Text::new(format!("Turns Left: {}", budget.0)),
                         TurnsText,
                         TextFont {
                             font: font.title.clone(),
@@ -131,7 +341,8 @@ fn setup(
                     grid_template_rows: RepeatedGridTrack::flex(8, 1.0),
                     ..default()
                 },
-                BackgroundColor(DARK),
+                BackgroundColor(palette.dark),
+                BoardFrame,
             ))
             .with_children(|p| {
                 for x in 0_i32..8 {
@@ -151,48 +362,33 @@ fn setup(
                                 align_items: AlignItems::End,
                                 ..default()
                             },
-                            BackgroundColor(if (x + y) % 2 == 0 { LIGHT } else { DARK }),
+                            BackgroundColor(if (x + y) % 2 == 0 {
+                                palette.light
+                            } else {
+                                palette.dark
+                            }),
                             Interaction::None,
                             ZIndex(10),
                         ));
+                        square
+                            .observe(start_drag)
+                            .observe(drag_piece)
+                            .observe(drop_piece)
+                            .observe(end_drag);
 
                         chessgrid.squares[x as usize][y as usize] = Some(square.id());
 
-                        let Some((color, kind)) = get_piece(x, y) else {
+                        let Some(piece) = layout.0.get(grid_coords) else {
                             continue;
                         };
 
-                        let (fg, bg, health) = match kind {
-                            PieceKind::Pawn => (fg.pawn.clone(), bg.pawn.clone(), PAWN_HEALTH),
-                            PieceKind::Knight => {
-                                (fg.knight.clone(), bg.knight.clone(), KNIGHT_HEALTH)
-                            }
-                            PieceKind::Bishop => {
-                                (fg.bishop.clone(), bg.bishop.clone(), BISHOP_HEALTH)
-                            }
-                            PieceKind::Rook => (fg.rook.clone(), bg.rook.clone(), ROOK_HEALTH),
-                            PieceKind::Queen => (fg.queen.clone(), bg.queen.clone(), QUEEN_HEALTH),
-                            PieceKind::King => (fg.king.clone(), bg.king.clone(), KING_HEALTH),
-                        };
-
-                        square.insert(spawn_piece_node(color, bg, fg));
-                        let mut piece = None;
-                        square.with_children(|p| {
-                            let id = p
-                                .spawn((
-                                    Name::new("Piece"),
-                                    Piece {
-                                        color,
-                                        kind,
-                                        health,
-                                    },
-                                ))
-                                .id();
-
-                            piece = Some(id);
-                        });
-
-                        chessgrid.pieces[x as usize][y as usize] = piece;
+                        chessgrid.pieces[x as usize][y as usize] = Some(insert_piece(
+                            &mut square,
+                            piece,
+                            &fg,
+                            &bg,
+                            font.title.clone(),
+                        ));
                     }
                 }
             });
@@ -257,9 +453,9 @@ fn setup(
                     .with_children(|p| {
                         p.spawn((
                             Name::new("Character Bg"),
+                            PieceNodeBg(PieceColor::Black),
                             ImageNode {
                                 image: bg.queen.clone(),
-                                color: Color::hsl(10.0, 1.0, 0.25),
                                 ..default()
                             },
                             Node {
@@ -285,13 +481,39 @@ fn setup(
             });
         });
 
+    commands.spawn((
+        Name::new("Hover Tooltip"),
+        HoverTooltip,
+        DespawnOnExit(AppState::Main),
+        Node {
+            position_type: PositionType::Absolute,
+            display: Display::None,
+            padding: px(8.0).into(),
+            ..default()
+        },
+        Text::default(),
+        TextFont {
+            font: font.title.clone(),
+            font_size: 22.0,
+            ..default()
+        },
+        BackgroundColor(Color::hsla(0.0, 0.0, 0.0, 0.8)),
+        GlobalZIndex(60),
+        Pickable::IGNORE,
+    ));
+
     commands.insert_resource(chessgrid);
+    commands.insert_resource(TurnsStat(budget.0));
+    commands.insert_resource(ActiveSide::default());
+    commands.insert_resource(BoardCursor::default());
+    commands.insert_resource(MoveAnimations::default());
 }
 
 fn interact(
     mut commands: Commands,
     mut chessgrid: ResMut<ChessGrid>,
     mut turns: ResMut<TurnsStat>,
+    mut moved: MessageWriter<PieceMoved>,
     query: Query<
         (Entity, &Interaction, &GridCoords, Option<&LegalSquare>),
         (With<TileGrid>, Changed<Interaction>),
@@ -299,7 +521,6 @@ fn interact(
     children: Query<&Children>,
     selected: Query<(Entity, &GridCoords), With<SelectedSquare>>,
     legal_tiles: Query<Entity, With<LegalSquare>>,
-    pieces: Query<&Piece>,
 ) {
     for (clicked_entity, interaction, clicked_coords, is_legal) in &query {
         if *interaction != Interaction::Pressed {
@@ -315,61 +536,331 @@ fn interact(
                 return;
             };
 
-            let Ok(from_children) = children.get(from_entity) else {
+            if chessgrid.get_piece(*from_coords).is_none() {
                 return;
-            };
+            }
 
-            let mut piece_entity = None;
-            let mut piece_nodes = Vec::new();
+            complete_move(
+                &mut commands,
+                &mut chessgrid,
+                &children,
+                &mut turns,
+                &mut moved,
+                std::iter::once(from_entity).chain(&legal_tiles),
+                *from_coords,
+                *clicked_coords,
+                true,
+            );
+            return;
+        }
 
-            for child in from_children.iter() {
-                if pieces.get(child).is_ok() {
-                    piece_entity = Some(child);
-                } else {
-                    if commands.get_entity(child).is_ok() {
-                        piece_nodes.push(child);
-                    }
-                }
-            }
+        for (entity, _) in &selected {
+            commands.entity(entity).remove::<SelectedSquare>();
+        }
 
-            let Some(piece_entity) = piece_entity else {
-                return;
-            };
+        commands.entity(clicked_entity).insert(SelectedSquare);
+    }
+}
 
-            chessgrid.pieces[from_coords.0.x as usize][from_coords.0.y as usize] = None;
-            chessgrid.pieces[clicked_coords.0.x as usize][clicked_coords.0.y as usize] =
-                Some(piece_entity);
+/// Picks up a piece of the side to move.
+fn start_drag(
+    event: On<Pointer<DragStart>>,
+    mut commands: Commands,
+    state: Res<State<BoardState>>,
+    chessgrid: Res<ChessGrid>,
+    active: Res<ActiveSide>,
+    turns: Res<TurnsStat>,
+    tiles: Query<&GridCoords, With<TileGrid>>,
+    selected: Query<Entity, With<SelectedSquare>>,
+    children: Query<&Children>,
+    pieces: Query<&Piece>,
+) {
+    if *state.get() != BoardState::Playing || event.button != PointerButton::Primary || turns.0 == 0
+    {
+        return;
+    }
 
-            commands.entity(clicked_entity).add_child(piece_entity);
+    let Ok(coords) = tiles.get(event.entity) else {
+        return;
+    };
 
-            for node in piece_nodes {
-                commands.entity(clicked_entity).add_child(node);
-            }
+    let piece = chessgrid
+        .get_piece(*coords)
+        .and_then(|entity| pieces.get(entity).ok());
 
-            commands.entity(from_entity).remove::<SelectedSquare>();
+    if piece.is_none_or(|piece| piece.color != active.0) {
+        return;
+    }
 
-            for entity in &legal_tiles {
-                commands.entity(entity).remove::<LegalSquare>();
-            }
+    for entity in &selected {
+        commands.entity(entity).remove::<SelectedSquare>();
+    }
+    commands.entity(event.entity).insert(SelectedSquare);
+
+    let nodes: Vec<Entity> = children
+        .get(event.entity)
+        .map(|children| {
+            children
+                .iter()
+                .filter(|child| pieces.get(*child).is_err())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Out of the way of picking, so the square underneath receives the drop.
+    for node in &nodes {
+        commands
+            .entity(*node)
+            .insert((Pickable::IGNORE, GlobalZIndex(50)));
+    }
 
-            turns.0 -= 1;
-            return;
+    commands.insert_resource(Dragging {
+        from: *coords,
+        nodes,
+        dropped: false,
+    });
+}
+
+fn drag_piece(
+    event: On<Pointer<Drag>>,
+    dragging: Option<Res<Dragging>>,
+    mut transforms: Query<&mut UiTransform>,
+) {
+    let Some(dragging) = dragging else {
+        return;
+    };
+
+    for node in &dragging.nodes {
+        if let Ok(mut transform) = transforms.get_mut(*node) {
+            transform.translation = Val2::px(event.distance.x, event.distance.y);
         }
+    }
+}
 
-        for (entity, _) in &selected {
-            commands.entity(entity).remove::<SelectedSquare>();
+/// Moves the dragged piece onto a legal square, just like clicking it would.
+fn drop_piece(
+    event: On<Pointer<DragDrop>>,
+    mut commands: Commands,
+    mut chessgrid: ResMut<ChessGrid>,
+    mut turns: ResMut<TurnsStat>,
+    mut moved: MessageWriter<PieceMoved>,
+    dragging: Option<ResMut<Dragging>>,
+    tiles: Query<(&GridCoords, Has<LegalSquare>), With<TileGrid>>,
+    selected: Query<Entity, With<SelectedSquare>>,
+    legal_tiles: Query<Entity, With<LegalSquare>>,
+    children: Query<&Children>,
+) {
+    let Some(mut dragging) = dragging else {
+        return;
+    };
+
+    let Ok((to, legal)) = tiles.get(event.entity) else {
+        return;
+    };
+
+    if dragging.dropped || !legal || turns.0 == 0 {
+        return;
+    }
+
+    complete_move(
+        &mut commands,
+        &mut chessgrid,
+        &children,
+        &mut turns,
+        &mut moved,
+        selected.iter().chain(&legal_tiles),
+        dragging.from,
+        *to,
+        false,
+    );
+    dragging.dropped = true;
+}
+
+/// Moves the selected piece from `from` to `to` and spends a turn, sliding it there if `slide`.
+fn complete_move(
+    commands: &mut Commands,
+    chessgrid: &mut ChessGrid,
+    children: &Query<&Children>,
+    turns: &mut TurnsStat,
+    moved: &mut MessageWriter<PieceMoved>,
+    highlighted: impl IntoIterator<Item = Entity>,
+    from: GridCoords,
+    to: GridCoords,
+    slide: bool,
+) {
+    if slide {
+        move_piece_entity(commands, chessgrid, children, from, to);
+    } else {
+        reparent_piece(commands, chessgrid, children, from, to);
+    }
+
+    for entity in highlighted {
+        commands
+            .entity(entity)
+            .remove::<(SelectedSquare, LegalSquare)>();
+    }
+
+    turns.0 -= 1;
+    moved.write(PieceMoved { from, to });
+}
+
+/// Moves the cursor with [`Nav`] and selects or moves to the square under it.
+fn keyboard_cursor(
+    mut commands: Commands,
+    mut cursor: ResMut<BoardCursor>,
+    mut chessgrid: ResMut<ChessGrid>,
+    mut turns: ResMut<TurnsStat>,
+    mut moved: MessageWriter<PieceMoved>,
+    actions: Actions,
+    active: Res<ActiveSide>,
+    selected: Query<(Entity, &GridCoords), With<SelectedSquare>>,
+    legal_tiles: Query<(Entity, &GridCoords), With<LegalSquare>>,
+    children: Query<&Children>,
+    pieces: Query<&Piece>,
+) {
+    let step: IVec2 = [
+        (Nav::Up, IVec2::NEG_Y),
+        (Nav::Down, IVec2::Y),
+        (Nav::Left, IVec2::NEG_X),
+        (Nav::Right, IVec2::X),
+    ]
+    .into_iter()
+    .filter(|(nav, _)| actions.nav_just_pressed(*nav))
+    .map(|(_, step)| step)
+    .sum();
+
+    if step != IVec2::ZERO {
+        // The cursor first appears on the selected square, or in front of white's king.
+        let start = cursor
+            .0
+            .or_else(|| selected.single().ok().map(|(_, coords)| *coords))
+            .unwrap_or(GridCoords::new(4, 6));
+
+        cursor.0 = Some(GridCoords(
+            (start.0 + step).clamp(IVec2::ZERO, IVec2::splat(7)),
+        ));
+    }
+
+    let forwards = actions.nav_just_pressed(Nav::Next);
+    let backwards = actions.nav_just_pressed(Nav::Previous);
+
+    if forwards || backwards {
+        let own: Vec<GridCoords> = (0..8)
+            .flat_map(|y| (0..8).map(move |x| GridCoords::new(x, y)))
+            .filter(|coords| {
+                chessgrid
+                    .get_piece(*coords)
+                    .and_then(|entity| pieces.get(entity).ok())
+                    .is_some_and(|piece| piece.color == active.0)
+            })
+            .collect();
+
+        let current = cursor
+            .0
+            .and_then(|coords| own.iter().position(|c| *c == coords));
+
+        let next = match current {
+            Some(index) if backwards => (index + own.len() - 1) % own.len(),
+            Some(index) => (index + 1) % own.len(),
+            None if backwards => own.len().saturating_sub(1),
+            None => 0,
+        };
+
+        if let Some(next) = own.get(next) {
+            cursor.0 = Some(*next);
+            select_square(&mut commands, &chessgrid, &selected, *next);
         }
+    }
 
-        commands.entity(clicked_entity).insert(SelectedSquare);
+    if !actions.nav_just_pressed(Nav::Select) {
+        return;
+    }
+
+    let Some(coords) = cursor.0 else {
+        return;
+    };
+
+    let legal = legal_tiles.iter().any(|(_, legal)| *legal == coords);
+
+    match selected.single() {
+        Ok((_, from)) if legal && turns.0 > 0 => complete_move(
+            &mut commands,
+            &mut chessgrid,
+            &children,
+            &mut turns,
+            &mut moved,
+            selected
+                .iter()
+                .chain(&legal_tiles)
+                .map(|(entity, _)| entity),
+            *from,
+            coords,
+            true,
+        ),
+        _ => select_square(&mut commands, &chessgrid, &selected, coords),
+    }
+}
+
+/// Selects `coords` as if it had been clicked.
+fn select_square(
+    commands: &mut Commands,
+    chessgrid: &ChessGrid,
+    selected: &Query<(Entity, &GridCoords), With<SelectedSquare>>,
+    coords: GridCoords,
+) {
+    for (entity, _) in selected {
+        commands.entity(entity).remove::<SelectedSquare>();
+    }
+
+    commands
+        .entity(chessgrid.get_square(coords))
+        .insert(SelectedSquare);
+}
+
+/// Puts the sprites back on their square.
+fn end_drag(
+    _event: On<Pointer<DragEnd>>,
+    mut commands: Commands,
+    dragging: Option<Res<Dragging>>,
+    mut transforms: Query<&mut UiTransform>,
+    piece_nodes: Query<(), With<PieceNode>>,
+) {
+    let Some(dragging) = dragging else {
+        return;
+    };
+
+    for node in &dragging.nodes {
+        if let Ok(mut transform) = transforms.get_mut(*node) {
+            transform.translation = Val2::ZERO;
+        }
+
+        let Ok(mut entity) = commands.get_entity(*node) else {
+            continue;
+        };
+
+        entity.remove::<GlobalZIndex>();
+
+        // Level badges are never pickable.
+        if piece_nodes.contains(*node) {
+            entity.remove::<Pickable>();
+        }
     }
+
+    commands.remove_resource::<Dragging>();
 }
 
+/// [`Action::Deselect`] clears the selection, or opens the pause menu when nothing is selected.
 fn deselect(
     mut commands: Commands,
-    keys: Res<ButtonInput<KeyCode>>,
+    mut pause: ResMut<NextState<PauseState>>,
+    actions: Actions,
     selected: Query<Entity, With<SelectedSquare>>,
 ) {
-    if keys.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(Action::Deselect) {
+        if selected.is_empty() {
+            pause.set(PauseState::Paused);
+        }
+
         for entity in &selected {
             commands.entity(entity).remove::<SelectedSquare>();
         }
@@ -380,6 +871,7 @@ fn find_legal_moves(
     mut commands: Commands,
     chessgrid: Res<ChessGrid>,
     turns: Res<TurnsStat>,
+    active: Res<ActiveSide>,
     children: Query<&Children>,
     selected_tile: Query<(Entity, &GridCoords), With<SelectedSquare>>,
     attacked_tiles: Query<Entity, With<AttackedSquare>>,
@@ -415,15 +907,8 @@ fn find_legal_moves(
         return;
     };
 
-    let moves = if piece.color == PieceColor::White && turns.0 > 0 {
-        match piece.kind {
-            PieceKind::Pawn => WhitePawnBehaviour::get_legal_moves(*grid_coords, *chessgrid),
-            PieceKind::Knight => KnightBehaviour::get_legal_moves(*grid_coords, *chessgrid),
-            PieceKind::Bishop => BishopBehaviour::get_legal_moves(*grid_coords, *chessgrid),
-            PieceKind::Rook => RookBehaviour::get_legal_moves(*grid_coords, *chessgrid),
-            PieceKind::Queen => QueenBehaviour::get_legal_moves(*grid_coords, *chessgrid),
-            PieceKind::King => KingBehaviour::get_legal_moves(*grid_coords, *chessgrid),
-        }
+    let moves = if piece.color == active.0 && turns.0 > 0 {
+        legal_moves(piece, *grid_coords, *chessgrid)
     } else {
         HashSet::default()
     };
@@ -432,34 +917,122 @@ fn find_legal_moves(
         let square_entity = chessgrid.get_square(coords);
         commands.entity(square_entity).insert(LegalSquare);
     }
-    let attacks = match piece.kind {
-        PieceKind::Pawn => {
-            if piece.color == PieceColor::White {
-                WhitePawnBehaviour::get_attacks(*grid_coords, *chessgrid)
-            } else {
-                BlackPawnBehaviour::get_attacks(*grid_coords, *chessgrid)
-            }
+    for coords in attacks(piece, *grid_coords, *chessgrid) {
+        if let Some(target_entity) = chessgrid.get_piece(coords)
+            && let Ok(target_piece) = pieces.get(target_entity)
+            && target_piece.color != piece.color
+        {
+            let square_entity = chessgrid.get_square(coords);
+            commands.entity(square_entity).insert(AttackedSquare);
         }
-        PieceKind::Knight => KnightBehaviour::get_attacks(*grid_coords, *chessgrid),
-        PieceKind::Bishop => BishopBehaviour::get_attacks(*grid_coords, *chessgrid),
-        PieceKind::Rook => RookBehaviour::get_attacks(*grid_coords, *chessgrid),
-        PieceKind::Queen => QueenBehaviour::get_attacks(*grid_coords, *chessgrid),
-        PieceKind::King => KingBehaviour::get_attacks(*grid_coords, *chessgrid),
+    }
+}
+
+/// Shows the hovered piece's stats and marks the squares it attacks.
+fn preview_hovered_piece(
+    mut commands: Commands,
+    mut tooltip: Query<(&mut Node, &mut Text), With<HoverTooltip>>,
+    chessgrid: Res<ChessGrid>,
+    dragging: Option<Res<Dragging>>,
+    tiles: Query<(&GridCoords, &Interaction), With<TileGrid>>,
+    previewed: Query<Entity, With<PreviewSquare>>,
+    pieces: Query<&Piece>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    for entity in &previewed {
+        commands.entity(entity).remove::<PreviewSquare>();
+    }
+
+    let Ok((mut node, mut text)) = tooltip.single_mut() else {
+        return;
     };
 
-    for coords in attacks {
-        if let Some(target_entity) = chessgrid.get_piece(coords) {
-            if let Ok(target_piece) = pieces.get(target_entity) {
-                if target_piece.color != piece.color {
-                    let square_entity = chessgrid.get_square(coords);
-                    commands.entity(square_entity).insert(AttackedSquare);
-                }
-            }
-        }
+    let hovered = tiles
+        .iter()
+        .find(|(_, interaction)| **interaction != Interaction::None)
+        .and_then(|(coords, _)| {
+            let piece = pieces.get(chessgrid.get_piece(*coords)?).ok()?;
+            Some((*coords, *piece))
+        });
+    let cursor = window.single().ok().and_then(Window::cursor_position);
+
+    let (Some((coords, piece)), Some(cursor), None) = (hovered, cursor, dragging) else {
+        node.display = Display::None;
+        return;
+    };
+
+    for target in attacks(piece, coords, *chessgrid) {
+        commands
+            .entity(chessgrid.get_square(target))
+            .insert(PreviewSquare);
+    }
+
+    let tooltip_text = format!(
+        "{:?} {:?}\nHealth: {}/{}\nDamage: {}",
+        piece.color,
+        piece.kind,
+        piece.health,
+        piece.max_health(),
+        piece.damage
+    );
+    if text.0 != tooltip_text {
+        text.0 = tooltip_text;
+    }
+
+    node.display = Display::Flex;
+    node.left = px(cursor.x + 16.0);
+    node.top = px(cursor.y + 16.0);
+}
+
+fn hide_tooltip(
+    mut commands: Commands,
+    mut tooltip: Query<&mut Node, With<HoverTooltip>>,
+    previewed: Query<Entity, With<PreviewSquare>>,
+) {
+    for mut node in &mut tooltip {
+        node.display = Display::None;
+    }
+
+    for entity in &previewed {
+        commands.entity(entity).remove::<PreviewSquare>();
+    }
+}
+
+fn toggle_heatmaps(mut heatmaps: ResMut<Heatmaps>, actions: Actions) {
+    if actions.just_pressed(Action::ToggleThreats) {
+        heatmaps.threat = !heatmaps.threat;
+    }
+
+    if actions.just_pressed(Action::ToggleCoverage) {
+        heatmaps.coverage = !heatmaps.coverage;
     }
 }
 
+fn refresh_heatmaps(
+    heatmaps: Res<Heatmaps>,
+    chessgrid: Res<ChessGrid>,
+    active: Res<ActiveSide>,
+    pieces: Query<&Piece>,
+    mut values: ResMut<HeatmapValues>,
+) {
+    let board = || chessgrid.to_board(|entity| pieces.get(entity).ok().copied());
+    values.threat = heatmaps
+        .threat
+        .then(|| board().threat_map(active.0.opponent()));
+    values.coverage = heatmaps.coverage.then(|| board().threat_map(active.0));
+}
+
+fn heatmap_tint(color: Color, tint: Color, damage: f32) -> Color {
+    color.mix(
+        &tint,
+        HEATMAP_STRENGTH * (damage / HEATMAP_FULL_DAMAGE).min(1.0),
+    )
+}
+
 fn update_tile_colors(
+    settings: Res<Settings>,
+    cursor: Res<BoardCursor>,
+    heatmaps: Res<HeatmapValues>,
     mut query: Query<
         (
             &GridCoords,
@@ -467,24 +1040,50 @@ fn update_tile_colors(
             Option<&SelectedSquare>,
             Option<&LegalSquare>,
             Option<&AttackedSquare>,
+            Has<PreviewSquare>,
             &mut BackgroundColor,
         ),
         With<TileGrid>,
     >,
 ) {
-    for (grid, interaction, selected, legal, attack, mut bg) in &mut query {
-        bg.0 = if selected.is_some() {
-            SELECT
+    let palette = settings.theme.palette();
+
+    for (grid, interaction, selected, legal, attack, preview, mut bg) in &mut query {
+        let color = if selected.is_some() {
+            palette.select
         } else if legal.is_some() {
-            LEGAL
+            palette.legal
         } else if attack.is_some() {
-            ATTACK
+            palette.attack
         } else if *interaction == Interaction::Hovered {
-            HOVER
+            palette.hover
         } else if grid.0.element_sum() % 2 == 0 {
-            LIGHT
+            palette.light
+        } else {
+            palette.dark
+        };
+
+        // Overlays only show through squares with nothing more important to say.
+        let plain = selected.is_none() && legal.is_none() && attack.is_none();
+        let [x, y] = grid.0.to_array().map(|i| i as usize);
+        let mut color = color;
+
+        if plain && let Some(threat) = heatmaps.threat {
+            color = heatmap_tint(color, palette.threat, threat[x][y]);
+        }
+
+        if plain && let Some(coverage) = heatmaps.coverage {
+            color = heatmap_tint(color, palette.coverage, coverage[x][y]);
+        }
+
+        if plain && preview {
+            color = color.mix(&palette.attack, PREVIEW_STRENGTH);
+        }
+
+        bg.0 = if cursor.0 == Some(*grid) {
+            color.mix(&palette.cursor, 0.6)
         } else {
-            DARK
+            color
         };
     }
 }
@@ -494,6 +1093,7 @@ fn update_selected_text(
     children: Query<&Children>,
     selected_tile: Query<Entity, With<SelectedSquare>>,
     pieces: Query<&Piece>,
+    actions: Actions,
 ) {
     let mut text = text_query.single_mut().unwrap();
 
@@ -506,8 +1106,15 @@ fn update_selected_text(
         for child in children.iter() {
             if let Ok(piece) = pieces.get(child) {
                 text.0 = format!(
-                    "Selected:\n{:?} {:?}\nHealth: {}\nPress [ESC]\nto deselect",
-                    piece.color, piece.kind, piece.health
+                    "Selected:\n{:?} {:?}\nHealth: {}/{}\nDamage: {}\nLevel: {} ({} XP)\nPress {}\nto deselect",
+                    piece.color,
+                    piece.kind,
+                    piece.health,
+                    piece.max_health(),
+                    piece.damage,
+                    piece.level,
+                    piece.xp,
+                    actions.prompt(Action::Deselect)
                 );
                 return;
             }
@@ -517,110 +1124,163 @@ fn update_selected_text(
     text.0 = "Selected: Empty".to_string();
 }
 
-fn update_turns_text(mut text_query: Query<&mut Text, With<TurnsText>>, turns: Res<TurnsStat>) {
-    let mut text = text_query.single_mut().unwrap();
-    text.0 = format!("Turns Left: {}", turns.0);
-}
-
-fn pass_turn(
-    mut commands: Commands,
-    mut chessgrid: ResMut<ChessGrid>,
-    mut turns: ResMut<TurnsStat>,
-    mut pieces: Query<(Entity, &mut Piece)>,
-    mut bubble_query: Query<(&mut Typewriter, &mut Text), With<QueenBubbleText>>,
-    keys: Res<ButtonInput<KeyCode>>,
-    sounds: Res<SoundsCollection>,
+fn update_level_badges(
+    pieces: Query<(&Piece, &ChildOf), Changed<Piece>>,
     children: Query<&Children>,
-    tiles: Query<(Entity, &GridCoords), With<TileGrid>>,
+    mut badges: Query<&mut Text, With<LevelBadge>>,
 ) {
-    if !keys.just_pressed(KeyCode::KeyP) {
-        return;
-    }
+    for (piece, child_of) in &pieces {
+        let Ok(siblings) = children.get(child_of.parent()) else {
+            continue;
+        };
 
-    commands.spawn(SamplePlayer::new(sounds.passturn.clone()));
+        for sibling in siblings.iter() {
+            if let Ok(mut text) = badges.get_mut(sibling) {
+                text.0 = level_badge_text(piece.level);
+            }
+        }
+    }
+}
 
-    apply_damage_for_color(
-        &mut commands,
-        &mut chessgrid,
-        &mut pieces,
-        &children,
-        PieceColor::White,
-        sounds.vineboom.clone(),
-    );
+fn toggle_health_bars(mut settings: ResMut<Settings>) {
+    settings.health_bars = !settings.health_bars;
+}
 
-    let mut rng = rand::rng();
+fn update_health_bars(
+    pieces: Query<(&Piece, &ChildOf), Changed<Piece>>,
+    children: Query<&Children>,
+    mut fills: Query<&mut Node, With<HealthBarFill>>,
+) {
+    for (piece, child_of) in &pieces {
+        let Ok(siblings) = children.get(child_of.parent()) else {
+            continue;
+        };
 
-    for _ in 0..3 {
-        for _ in 0..64 {
-            let x = rng.random_range(0..8);
-            let y = rng.random_range(0..8);
+        for sibling in siblings.iter() {
+            if let Ok(mut node) = fills.get_mut(sibling) {
+                node.width = health_bar_width(piece);
+            }
+        }
+    }
+}
 
-            let from = GridCoords::new(x, y);
-            let Some(piece_ent) = chessgrid.get_piece(from) else {
-                continue;
+fn show_health_bars(settings: Res<Settings>, mut bars: Query<(&mut Visibility, Ref<HealthBar>)>) {
+    for (mut visibility, bar) in &mut bars {
+        if settings.is_changed() || bar.is_added() {
+            *visibility = if settings.health_bars {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
             };
+        }
+    }
+}
 
-            let (_, piece) = pieces.get(piece_ent).unwrap();
-
-            if piece.color == PieceColor::White {
-                continue;
-            }
+/// The fill spans the track's width at full health.
+fn health_bar_width(piece: &Piece) -> Val {
+    percent(80.0 * (piece.health / piece.max_health()).clamp(0.0, 1.0))
+}
 
-            let moves = match piece.kind {
-                PieceKind::Pawn => BlackPawnBehaviour::get_legal_moves(from, *chessgrid),
-                PieceKind::Knight => KnightBehaviour::get_legal_moves(from, *chessgrid),
-                PieceKind::Bishop => BishopBehaviour::get_legal_moves(from, *chessgrid),
-                PieceKind::Rook => RookBehaviour::get_legal_moves(from, *chessgrid),
-                PieceKind::Queen => QueenBehaviour::get_legal_moves(from, *chessgrid),
-                PieceKind::King => KingBehaviour::get_legal_moves(from, *chessgrid),
-            };
+fn level_badge_text(level: u8) -> String {
+    if level > 1 {
+        format!("Lv{level}")
+    } else {
+        String::new()
+    }
+}
 
-            let len = moves.len();
+fn update_turns_text(
+    mut text_query: Query<&mut Text, With<TurnsText>>,
+    turns: Res<TurnsStat>,
+    active: Res<ActiveSide>,
+    opponent: Res<Opponent>,
+) {
+    let mut text = text_query.single_mut().unwrap();
+    text.0 = if *opponent == Opponent::Human {
+        format!("{:?} to move\nTurns Left: {}", active.0, turns.0)
+    } else {
+        format!("Turns Left: {}", turns.0)
+    };
+}
 
-            if len == 0 {
-                continue;
-            }
+fn pass_text(actions: &Actions) -> String {
+    format!(
+        "Press {} to Pass\nor End Turn\n{} Health Bars\n{} Threats\n{} Coverage\n{} Menu",
+        actions.prompt(Action::Pass),
+        actions.prompt(Action::ToggleHealthBars),
+        actions.prompt(Action::ToggleThreats),
+        actions.prompt(Action::ToggleCoverage),
+        actions.prompt(Action::Deselect)
+    )
+}
 
-            let idx = rng.random_range(0..len);
-            let to = moves.iter().nth(idx).unwrap();
+fn pass_turn(mut pass: MessageWriter<PassRequested>) {
+    pass.write(PassRequested);
+}
 
-            chessgrid.pieces[from.0.x as usize][from.0.y as usize] = None;
-            chessgrid.pieces[to.0.x as usize][to.0.y as usize] = Some(piece_ent);
+fn resolve_pass(
+    mut commands: Commands,
+    mut pass_requested: MessageReader<PassRequested>,
+    mut chessgrid: ResMut<ChessGrid>,
+    mut turns: ResMut<TurnsStat>,
+    mut pieces: Query<(Entity, &mut Piece)>,
+    mut bubble_query: Query<(&mut Typewriter, &mut Text), With<QueenBubbleText>>,
+    mut side_passed: MessageWriter<SidePassed>,
+    mut turn_passed: MessageWriter<TurnPassed>,
+    mut active: ResMut<ActiveSide>,
+    mut board_state: ResMut<NextState<BoardState>>,
+    opponent: Res<Opponent>,
+    budget: Res<ActionBudget>,
+    sounds: Res<SoundsCollection>,
+    fonts: Res<FontsCollection>,
+    children: Query<&Children>,
+) {
+    // Several requests in one frame still only pass once.
+    pass_requested.clear();
 
-            let mut from_tile = None;
-            let mut to_tile = None;
+    commands.spawn(SamplePlayer::new(sounds.passturn.clone()));
 
-            for (tile_entity, coords) in &tiles {
-                if *coords == from {
-                    from_tile = Some(tile_entity);
-                }
-                if *coords == *to {
-                    to_tile = Some(tile_entity);
-                }
-            }
+    apply_damage_for_color(
+        &mut commands,
+        &mut chessgrid,
+        &mut pieces,
+        &children,
+        active.0,
+        sounds.vineboom.clone(),
+        &fonts.title,
+    );
 
-            let (Some(from_tile), Some(to_tile)) = (from_tile, to_tile) else {
-                return;
-            };
+    // Each player passes on their own, and so does a bot. At a shared machine the board is hidden
+    // while they swap seats.
+    if matches!(
+        *opponent,
+        Opponent::Human | Opponent::Remote | Opponent::External
+    ) {
+        side_passed.write(SidePassed(active.0));
+        active.0 = active.0.opponent();
+        turns.0 = budget.0;
+
+        if *opponent == Opponent::Human {
+            board_state.set(BoardState::HandOff);
+        }
 
-            let mut extra_nodes = Vec::new();
+        if active.0 == PieceColor::White {
+            turn_passed.write(TurnPassed);
+        }
 
-            if let Ok(from_children) = children.get(from_tile) {
-                for child in from_children.iter() {
-                    if child != piece_ent {
-                        extra_nodes.push(child);
-                    }
-                }
-            }
+        return;
+    }
 
-            commands.entity(to_tile).add_child(piece_ent);
+    let mut rng = rand::rng();
 
-            for node in extra_nodes {
-                commands.entity(to_tile).add_child(node);
-            }
+    for _ in 0..TURNS_PER_PASS {
+        let board = chessgrid.to_board(|entity| pieces.get(entity).ok().map(|(_, p)| *p));
 
+        let Some((from, to)) = opponent.choose_move(&board, PieceColor::Black, &mut rng) else {
             break;
-        }
+        };
+
+        move_piece_entity(&mut commands, &mut chessgrid, &children, from, to);
     }
 
     apply_damage_for_color(
@@ -630,6 +1290,7 @@ fn pass_turn(
         &children,
         PieceColor::Black,
         sounds.vineboom.clone(),
+        &fonts.title,
     );
 
     if let Ok((mut typewriter, mut text)) = bubble_query.single_mut() {
@@ -638,26 +1299,249 @@ fn pass_turn(
         typewriter.timer.reset();
         text.0.clear();
     }
-    turns.0 = 3;
+    turns.0 = budget.0;
+    turn_passed.write(TurnPassed);
+}
+
+/// Moves the piece on `from` and its sprites to `to`, keeping the [`ChessGrid`] in step. The
+/// sprites slide over once the moves queued before have played.
+pub fn move_piece_entity(
+    commands: &mut Commands,
+    chessgrid: &mut ChessGrid,
+    children: &Query<&Children>,
+    from: GridCoords,
+    to: GridCoords,
+) {
+    let Some(piece) = reparent_piece(commands, chessgrid, children, from, to) else {
+        return;
+    };
+
+    commands.queue(move |world: &mut World| {
+        world
+            .resource_mut::<MoveAnimations>()
+            .queue
+            .push_back(Slide { piece, from, to });
+    });
+}
+
+/// Moves the piece on `from` and its sprites to `to` at once, returning the piece.
+fn reparent_piece(
+    commands: &mut Commands,
+    chessgrid: &mut ChessGrid,
+    children: &Query<&Children>,
+    from: GridCoords,
+    to: GridCoords,
+) -> Option<Entity> {
+    let piece_ent = chessgrid.get_piece(from)?;
+
+    chessgrid.pieces[from.0.x as usize][from.0.y as usize] = None;
+    chessgrid.pieces[to.0.x as usize][to.0.y as usize] = Some(piece_ent);
+
+    let from_tile = chessgrid.get_square(from);
+    let to_tile = chessgrid.get_square(to);
+
+    let mut extra_nodes = Vec::new();
+
+    if let Ok(from_children) = children.get(from_tile) {
+        for child in from_children.iter() {
+            if child != piece_ent {
+                extra_nodes.push(child);
+            }
+        }
+    }
+
+    commands.entity(to_tile).add_child(piece_ent);
+
+    for node in extra_nodes {
+        commands.entity(to_tile).add_child(node);
+    }
+
+    Some(piece_ent)
+}
+
+/// Plays the queued [`Slide`]s one at a time.
+fn animate_moves(
+    mut animations: ResMut<MoveAnimations>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    dragging: Option<Res<Dragging>>,
+    pieces: Query<(&Piece, &ChildOf)>,
+    tiles: Query<(&GridCoords, &ComputedNode)>,
+    children: Query<&Children>,
+    mut transforms: Query<&mut UiTransform, Without<DamageNumber>>,
+) {
+    let animations = &mut *animations;
+    let mut finished = None;
+
+    if let Some((slide, timer)) = &mut animations.current {
+        timer.tick(time.delta());
+
+        if timer.is_finished() || !pieces.contains(slide.piece) {
+            finished = Some(slide.piece);
+            animations.current = None;
+        }
+    }
+
+    // Pieces that died before their turn came have nothing left to show.
+    while animations
+        .queue
+        .front()
+        .is_some_and(|slide| !pieces.contains(slide.piece))
+    {
+        animations.queue.pop_front();
+    }
+
+    if animations.current.is_none()
+        && let Some(slide) = animations.queue.pop_front()
+    {
+        animations.current = Some((
+            slide,
+            Timer::from_seconds(settings.move_duration.max(0.0), TimerMode::Once),
+        ));
+    }
+
+    // Where each animated piece is drawn and how high it hops, in squares, or `None` once it is
+    // back on its own square.
+    let mut drawn_at: HashMap<Entity, Option<(Vec2, f32)>> = HashMap::default();
+
+    if let Some((slide, timer)) = &animations.current {
+        let t = EaseFunction::CubicInOut.sample_clamped(timer.fraction());
+        let from = slide.from.0.as_vec2();
+        let to = slide.to.0.as_vec2();
+
+        let hop = match pieces.get(slide.piece) {
+            Ok((piece, _)) if piece.kind == PieceKind::Knight => {
+                HOP_HEIGHT * (PI * timer.fraction()).sin()
+            }
+            _ => 0.0,
+        };
+
+        drawn_at.insert(slide.piece, Some((from.lerp(to, t), hop)));
+    }
+
+    for slide in &animations.queue {
+        drawn_at
+            .entry(slide.piece)
+            .or_insert(Some((slide.from.0.as_vec2(), 0.0)));
+    }
+
+    if let Some(piece) = finished {
+        drawn_at.entry(piece).or_insert(None);
+    }
+
+    for (piece, drawn) in drawn_at {
+        let Ok((_, child_of)) = pieces.get(piece) else {
+            continue;
+        };
+        let Ok((coords, tile)) = tiles.get(child_of.parent()) else {
+            continue;
+        };
+
+        // A dragged piece follows the cursor instead.
+        if dragging
+            .as_ref()
+            .is_some_and(|dragging| dragging.from == *coords)
+        {
+            continue;
+        }
+
+        let translation = match drawn {
+            Some((position, hop)) => {
+                let size = tile.size() * tile.inverse_scale_factor();
+                let offset = (position - coords.0.as_vec2() - Vec2::new(0.0, hop)) * size;
+                Val2::px(offset.x, offset.y)
+            }
+            None => Val2::ZERO,
+        };
+
+        let Ok(siblings) = children.get(child_of.parent()) else {
+            continue;
+        };
+
+        for sibling in siblings.iter() {
+            if let Ok(mut transform) = transforms.get_mut(sibling) {
+                transform.translation = translation;
+            }
+        }
+    }
 }
 
-fn spawn_piece_node(color: PieceColor, bg: Handle<Image>, fg: Handle<Image>) -> impl Bundle {
-    let color = match color {
-        PieceColor::White => Color::hsl(175.0, 1.0, 0.75),
-        PieceColor::Black => Color::hsl(10.0, 1.0, 0.25),
+/// Adds `piece` and its sprites to an empty board square, returning the piece entity. The caller
+/// records it in the [`ChessGrid`].
+pub fn insert_piece(
+    square: &mut EntityCommands,
+    piece: Piece,
+    fg: &SpritesFgCollection,
+    bg: &SpritesBgCollection,
+    font: Handle<Font>,
+) -> Entity {
+    let (fg, bg) = match piece.kind {
+        PieceKind::Pawn => (fg.pawn.clone(), bg.pawn.clone()),
+        PieceKind::Knight => (fg.knight.clone(), bg.knight.clone()),
+        PieceKind::Bishop => (fg.bishop.clone(), bg.bishop.clone()),
+        PieceKind::Rook => (fg.rook.clone(), bg.rook.clone()),
+        PieceKind::Queen => (fg.queen.clone(), bg.queen.clone()),
+        PieceKind::King => (fg.king.clone(), bg.king.clone()),
     };
 
+    square.insert(spawn_piece_node(piece, bg, fg, font));
+    let piece_entity = square.commands().spawn((Name::new("Piece"), piece)).id();
+    square.add_child(piece_entity);
+
+    piece_entity
+}
+
+/// Replaces every piece on the board with the ones in `board`.
+pub fn rebuild_board(
+    commands: &mut Commands,
+    chessgrid: &mut ChessGrid,
+    children: &Query<&Children>,
+    board: &Board,
+    fg: &SpritesFgCollection,
+    bg: &SpritesBgCollection,
+    font: &FontsCollection,
+) {
+    for x in 0..8 {
+        for y in 0..8 {
+            let coords = GridCoords::new(x, y);
+            let square = chessgrid.get_square(coords);
+
+            if let Ok(square_children) = children.get(square) {
+                for child in square_children.iter() {
+                    commands.entity(child).despawn();
+                }
+            }
+
+            chessgrid.pieces[x as usize][y as usize] = board.get(coords).map(|piece| {
+                insert_piece(
+                    &mut commands.entity(square),
+                    piece,
+                    fg,
+                    bg,
+                    font.title.clone(),
+                )
+            });
+        }
+    }
+}
+
+fn spawn_piece_node(
+    piece: Piece,
+    bg: Handle<Image>,
+    fg: Handle<Image>,
+    font: Handle<Font>,
+) -> impl Bundle {
     children![
         (
             Name::new("Piece Node Bg"),
             PieceNode,
+            PieceNodeBg(piece.color),
             Node {
                 width: percent(100.0),
                 position_type: PositionType::Absolute,
                 ..default()
             },
             ImageNode {
-                color,
                 image: bg,
                 ..default()
             }
@@ -675,40 +1559,56 @@ fn spawn_piece_node(color: PieceColor, bg: Handle<Image>, fg: Handle<Image>) ->
                 ..default()
             }
         ),
+        (
+            Name::new("Piece Level Badge"),
+            LevelBadge,
+            Node {
+                position_type: PositionType::Absolute,
+                top: px(2.0),
+                right: px(4.0),
+                ..default()
+            },
+            Text::new(level_badge_text(piece.level)),
+            TextColor(Color::hsl(50.0, 1.0, 0.6)),
+            TextFont {
+                font,
+                font_size: 18.0,
+                ..default()
+            },
+            Pickable::IGNORE,
+        ),
+        (
+            Name::new("Health Bar Track"),
+            HealthBar,
+            Node {
+                position_type: PositionType::Absolute,
+                left: percent(10.0),
+                bottom: px(2.0),
+                width: percent(80.0),
+                height: px(6.0),
+                ..default()
+            },
+            BackgroundColor(Color::hsla(0.0, 0.0, 0.0, 0.6)),
+            Pickable::IGNORE,
+        ),
+        (
+            Name::new("Health Bar Fill"),
+            HealthBar,
+            HealthBarFill(piece.color),
+            Node {
+                position_type: PositionType::Absolute,
+                left: percent(10.0),
+                bottom: px(2.0),
+                width: health_bar_width(&piece),
+                height: px(6.0),
+                ..default()
+            },
+            BackgroundColor::DEFAULT,
+            Pickable::IGNORE,
+        ),
     ]
 }
 
-fn get_piece(x: i32, y: i32) -> Option<(PieceColor, PieceKind)> {
-    if y == 1 {
-        return Some((PieceColor::Black, PieceKind::Pawn));
-    }
-
-    if y == 6 {
-        return Some((PieceColor::White, PieceKind::Pawn));
-    }
-
-    let kind = match x {
-        0 | 7 => PieceKind::Rook,
-        1 | 6 => PieceKind::Knight,
-        2 | 5 => PieceKind::Bishop,
-        3 => PieceKind::Queen,
-        4 => PieceKind::King,
-        _ => {
-            unreachable!();
-        }
-    };
-
-    if y == 0 {
-        return Some((PieceColor::Black, kind));
-    }
-
-    if y == 7 {
-        return Some((PieceColor::White, kind));
-    }
-
-    None
-}
-
 fn apply_damage_for_color(
     commands: &mut Commands,
     chessgrid: &mut ChessGrid,
@@ -716,60 +1616,36 @@ fn apply_damage_for_color(
     children: &Query<&Children>,
     attacker_color: PieceColor,
     s: Handle<AudioSample>,
+    font: &Handle<Font>,
 ) {
-    const DAMAGE: f32 = 10.0;
-
-    let mut damage_events: Vec<(Entity, f32)> = Vec::new();
-
-    for x in 0..8 {
-        for y in 0..8 {
-            let from = GridCoords::new(x as i32, y as i32);
-
-            let Some(attacker_ent) = chessgrid.get_piece(from) else {
-                continue;
-            };
-
-            let Ok((_, attacker_piece)) = pieces_query.get_mut(attacker_ent) else {
-                continue;
-            };
-
-            if attacker_piece.color != attacker_color {
-                continue;
-            }
+    let board = chessgrid.to_board(|entity| pieces_query.get(entity).ok().map(|(_, p)| *p));
+
+    let damage_events: Vec<(Entity, Entity, GridCoords, f32)> = board
+        .damage_events(attacker_color)
+        .into_iter()
+        .filter_map(|hit| {
+            Some((
+                chessgrid.get_piece(hit.attacker)?,
+                chessgrid.get_piece(hit.target)?,
+                hit.target,
+                hit.damage,
+            ))
+        })
+        .collect();
 
-            let attacks = match attacker_piece.kind {
-                PieceKind::Pawn => {
-                    if attacker_piece.color == PieceColor::White {
-                        WhitePawnBehaviour::get_attacks(from, *chessgrid)
-                    } else {
-                        BlackPawnBehaviour::get_attacks(from, *chessgrid)
-                    }
-                }
-                PieceKind::Knight => KnightBehaviour::get_attacks(from, *chessgrid),
-                PieceKind::Bishop => BishopBehaviour::get_attacks(from, *chessgrid),
-                PieceKind::Rook => RookBehaviour::get_attacks(from, *chessgrid),
-                PieceKind::Queen => QueenBehaviour::get_attacks(from, *chessgrid),
-                PieceKind::King => KingBehaviour::get_attacks(from, *chessgrid),
-            };
+    // Hits on the same piece add up to one number.
+    let mut totals: HashMap<GridCoords, (Entity, f32)> = HashMap::default();
 
-            for target_pos in attacks {
-                if let Some(target_ent) = chessgrid.get_piece(target_pos) {
-                    if let Ok((_, target_piece)) = pieces_query.get_mut(target_ent) {
-                        if target_piece.color != attacker_color {
-                            damage_events.push((target_ent, DAMAGE));
-                        }
-                    }
-                }
-            }
-        }
-    }
+    for (attacker, entity, coords, dmg) in damage_events {
+        let mut xp = dmg as u32;
 
-    for (entity, dmg) in damage_events {
         if let Ok((ent, mut piece)) = pieces_query.get_mut(entity) {
+            let was_alive = piece.health > 0.0;
             piece.health -= dmg;
+            totals.entry(coords).or_insert((ent, 0.0)).1 += dmg;
 
-            if piece.health <= 0.0 {
-                commands.spawn(SamplePlayer::new(s.clone()));
+            if was_alive && piece.health <= 0.0 {
+                xp += KILL_XP;
                 for x in 0..8 {
                     for y in 0..8 {
                         if chessgrid.pieces[x][y] == Some(ent) {
@@ -780,12 +1656,24 @@ fn apply_damage_for_color(
 
                 for x in 0..8 {
                     for y in 0..8 {
-                        if let Some(square_ent) = chessgrid.squares[x][y] {
-                            if let Ok(tile_children) = children.get(square_ent) {
-                                if tile_children.contains(&ent) {
-                                    for child in tile_children.iter() {
-                                        commands.entity(child).despawn();
-                                    }
+                        if let Some(square_ent) = chessgrid.squares[x][y]
+                            && let Ok(tile_children) = children.get(square_ent)
+                            && tile_children.contains(&ent)
+                        {
+                            let dying = commands
+                                .spawn(Dying {
+                                    coords: GridCoords::new(x as i32, y as i32),
+                                    sound: s.clone(),
+                                    timer: None,
+                                })
+                                .id();
+                            commands.entity(square_ent).add_child(dying);
+
+                            for child in tile_children.iter() {
+                                if child == ent {
+                                    commands.entity(child).despawn();
+                                } else {
+                                    commands.entity(dying).add_child(child);
                                 }
                             }
                         }
@@ -793,5 +1681,203 @@ fn apply_damage_for_color(
                 }
             }
         }
+
+        if let Ok((_, mut attacker)) = pieces_query.get_mut(attacker) {
+            attacker.gain_xp(xp);
+        }
+    }
+
+    for (coords, (entity, total)) in totals {
+        let square = chessgrid.get_square(coords);
+        let number = commands.spawn(damage_number(total, font.clone())).id();
+        commands.entity(square).add_child(number);
+
+        if pieces_query
+            .get(entity)
+            .is_ok_and(|(_, piece)| piece.health > 0.0)
+        {
+            commands.entity(entity).insert(HitFlash(Timer::from_seconds(
+                HIT_FLASH_SECS,
+                TimerMode::Once,
+            )));
+        }
+    }
+}
+
+fn lift_dead_pieces(
+    mut commands: Commands,
+    dying: Query<(Entity, &Dying, &ChildOf), Added<Dying>>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+    piece_nodes: Query<(), With<PieceNode>>,
+) {
+    for (entity, dying, child_of) in &dying {
+        let Ok(board) = parents.get(child_of.parent()) else {
+            continue;
+        };
+
+        commands.entity(entity).insert((
+            Name::new("Dying Piece"),
+            Node {
+                width: percent(100.0),
+                height: percent(100.0),
+                grid_row: GridPlacement::start(dying.coords.0.y as i16 + 1),
+                grid_column: GridPlacement::start(dying.coords.0.x as i16 + 1),
+                overflow: Overflow::visible(),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::End,
+                ..default()
+            },
+            ZIndex(20),
+            Pickable::IGNORE,
+        ));
+        commands.entity(board.parent()).add_child(entity);
+
+        for child in children.get(entity).into_iter().flat_map(|c| c.iter()) {
+            if piece_nodes.contains(child) {
+                commands
+                    .entity(child)
+                    .insert((Pickable::IGNORE, UiTransform::IDENTITY));
+            } else {
+                commands.entity(child).despawn();
+            }
+        }
+    }
+}
+
+/// Waits for the board to catch up with the killing blow before splitting a piece apart.
+fn break_up_dead_pieces(
+    mut commands: Commands,
+    mut dying: Query<(Entity, &mut Dying)>,
+    animations: Res<MoveAnimations>,
+    children: Query<&Children>,
+    time: Res<Time>,
+) {
+    let settled = animations.current.is_none() && animations.queue.is_empty();
+
+    for (entity, mut dying) in &mut dying {
+        let Some(timer) = &mut dying.timer else {
+            if !settled {
+                continue;
+            }
+
+            commands.spawn(SamplePlayer::new(dying.sound.clone()));
+
+            let sprites = children.get(entity).into_iter().flat_map(|c| c.iter());
+            for (sprite, direction) in sprites.zip([-1.0, 1.0]) {
+                commands.entity(sprite).insert(Debris::new(direction));
+            }
+
+            dying.timer = Some(Timer::from_seconds(DEBRIS_SECS, TimerMode::Once));
+            continue;
+        };
+
+        timer.tick(time.delta());
+
+        if timer.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn damage_number(total: f32, font: Handle<Font>) -> impl Bundle {
+    (
+        Name::new("Damage Number"),
+        DamageNumber(Timer::from_seconds(DAMAGE_NUMBER_SECS, TimerMode::Once)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(0.0),
+            width: percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Text::new(format!("-{}", total.ceil())),
+        TextColor(HIT_FLASH),
+        TextFont {
+            font,
+            font_size: 28.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(Justify::Center),
+        UiTransform::IDENTITY,
+        GlobalZIndex(40),
+        Pickable::IGNORE,
+    )
+}
+
+fn float_damage_numbers(
+    mut commands: Commands,
+    mut numbers: Query<(Entity, &mut DamageNumber, &mut UiTransform, &mut TextColor)>,
+    time: Res<Time>,
+) {
+    for (entity, mut number, mut transform, mut color) in &mut numbers {
+        number.0.tick(time.delta());
+        let t = number.0.fraction();
+
+        transform.translation = Val2::px(0.0, -DAMAGE_NUMBER_RISE * t);
+        color.0 = HIT_FLASH.with_alpha(1.0 - t);
+
+        if number.0.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn flash_hits(
+    mut commands: Commands,
+    mut flashing: Query<(Entity, &ChildOf, &mut HitFlash)>,
+    mut nodes: Query<(&mut ImageNode, Option<&PieceNodeBg>), With<PieceNode>>,
+    children: Query<&Children>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    for (entity, child_of, mut flash) in &mut flashing {
+        flash.0.tick(time.delta());
+        let t = flash.0.fraction();
+
+        let Ok(siblings) = children.get(child_of.parent()) else {
+            continue;
+        };
+
+        for sibling in siblings.iter() {
+            if let Ok((mut image, bg)) = nodes.get_mut(sibling) {
+                let base = bg.map_or(Color::WHITE, |bg| settings.theme.palette().piece(bg.0));
+                image.color = HIT_FLASH.mix(&base, t);
+            }
+        }
+
+        if flash.0.is_finished() {
+            commands.entity(entity).remove::<HitFlash>();
+        }
+    }
+}
+
+/// Tints new piece backgrounds and health bars, or all of them when the theme changes.
+fn tint_pieces(
+    mut nodes: Query<(Ref<PieceNodeBg>, &mut ImageNode)>,
+    mut fills: Query<(Ref<HealthBarFill>, &mut BackgroundColor)>,
+    settings: Res<Settings>,
+) {
+    let palette = settings.theme.palette();
+
+    for (bg, mut image) in &mut nodes {
+        if settings.is_changed() || bg.is_added() {
+            image.color = palette.piece(bg.0);
+        }
+    }
+
+    for (fill, mut color) in &mut fills {
+        if settings.is_changed() || fill.is_added() {
+            color.0 = palette.piece(fill.0);
+        }
+    }
+}
+
+fn recolor_board_frame(
+    mut frames: Query<&mut BackgroundColor, With<BoardFrame>>,
+    settings: Res<Settings>,
+) {
+    for mut bg in &mut frames {
+        bg.0 = settings.theme.palette().dark;
     }
 }
@@ -1,6 +1,7 @@
 //! Development tools for the game. This plugin is only enabled in dev builds.
 
-use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use crate::actions::{Action, action_just_pressed};
+use bevy::prelude::*;
 use bevy_inspector_egui::{bevy_egui::EguiPlugin, quick::WorldInspectorPlugin};
 
 pub(super) fn plugin(app: &mut App) {
@@ -8,12 +9,10 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_systems(
         Update,
-        toggle_debug_ui.run_if(input_just_pressed(TOGGLE_KEY)),
+        toggle_debug_ui.run_if(action_just_pressed(Action::ToggleDebug)),
     );
 }
 
-const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
-
 fn toggle_debug_ui(mut options: ResMut<UiDebugOptions>) {
     options.toggle();
 }
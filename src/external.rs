@@ -0,0 +1,248 @@
+//! Either side driven by an external bot given on the command line, e.g.
+//! `--black-bot target/debug/examples/sample_bot`, in standard games. Bots talk the protocol in
+//! [`crate::bot`] and run on their own threads, so the game keeps drawing while they start up and
+//! think.
+
+use crate::{
+    AppState, BoardState, GameMode,
+    ai::Opponent,
+    behaviour::{Move, *},
+    bot::BotProcess,
+    chessboard::{ActiveSide, PassRequested, move_piece_entity},
+    stats::{ActionBudget, TURNS_PER_PASS, TurnsStat},
+};
+use bevy::prelude::*;
+use std::{
+    mem,
+    sync::{
+        Mutex,
+        mpsc::{self, Receiver, Sender, TryRecvError},
+    },
+    thread,
+};
+
+/// Pause between a white bot's turns, so games between bots can be followed.
+const WHITE_BOT_DELAY: f32 = 0.5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(Bots {
+        white: spawn_from_args("--white-bot"),
+        black: spawn_from_args("--black-bot"),
+    });
+
+    app.add_systems(
+        OnEnter(AppState::Main),
+        hand_black_to_bot.run_if(in_standard_mode),
+    );
+    app.add_systems(OnExit(AppState::Main), forget_requests);
+    app.add_systems(
+        Update,
+        (
+            drive_white.run_if(in_state(BoardState::Playing).and(has_white_bot)),
+            drive_black.run_if(
+                in_state(BoardState::Playing)
+                    .or(in_state(BoardState::Waiting))
+                    .and(has_black_bot),
+            ),
+        )
+            .run_if(in_state(AppState::Main).and(in_standard_mode)),
+    );
+}
+
+/// The bots given on the command line, one per side.
+#[derive(Resource, Default)]
+pub struct Bots {
+    pub white: Option<Bot>,
+    pub black: Option<Bot>,
+}
+
+/// A bot run by a [`BotProcess`] on its own thread. Turns are asked for with [`Bot::request`] and
+/// picked up with [`Bot::poll`] once the bot has answered.
+pub struct Bot {
+    requests: Sender<(Board, PieceColor, u8)>,
+    replies: Mutex<Receiver<Vec<Move>>>,
+    waiting: bool,
+    /// Whether the game the outstanding request was made for is over, so its reply is dropped.
+    stale: bool,
+}
+
+impl Bot {
+    /// Starts `command` on a new thread. Failing to start is logged there, and the bot then passes
+    /// every turn.
+    pub fn spawn(command: String) -> Self {
+        let (requests, request_receiver) = mpsc::channel::<(Board, PieceColor, u8)>();
+        let (reply_sender, replies) = mpsc::channel();
+
+        thread::spawn(move || {
+            let bot = match BotProcess::spawn(&command) {
+                Ok(bot) => bot,
+                Err(error) => {
+                    error!("Could not start bot {command}: {error}");
+                    return;
+                }
+            };
+
+            info!("Bot {} started from {command}", bot.name);
+
+            for (board, color, actions) in request_receiver {
+                let moves = bot.request_moves(&board, color, actions);
+
+                if reply_sender.send(moves).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            requests,
+            replies: Mutex::new(replies),
+            waiting: false,
+            stale: false,
+        }
+    }
+
+    /// Asks for up to `actions` moves for `color`, unless the bot is still busy with a turn.
+    pub fn request(&mut self, board: Board, color: PieceColor, actions: u8) {
+        if !self.waiting {
+            // A bot that has stopped is noticed by `poll`.
+            let _ = self.requests.send((board, color, actions));
+            self.waiting = true;
+        }
+    }
+
+    /// The moves answering the last request, once they have arrived. A bot that has stopped
+    /// passes.
+    pub fn poll(&mut self) -> Option<Vec<Move>> {
+        if !self.waiting {
+            return None;
+        }
+
+        let moves = match self.replies.get_mut().unwrap().try_recv() {
+            Ok(moves) => moves,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Vec::new(),
+        };
+
+        self.waiting = false;
+        (!mem::take(&mut self.stale)).then_some(moves)
+    }
+
+    fn forget(&mut self) {
+        self.stale = self.waiting;
+    }
+}
+
+/// Starts the bot named by the argument after `flag`, if there is one.
+fn spawn_from_args(flag: &str) -> Option<Bot> {
+    let mut args = std::env::args().skip_while(|arg| arg != flag).skip(1);
+
+    args.next().map(Bot::spawn)
+}
+
+fn in_standard_mode(mode: Res<GameMode>) -> bool {
+    *mode == GameMode::Standard
+}
+
+fn has_white_bot(bots: Res<Bots>) -> bool {
+    bots.white.is_some()
+}
+
+fn has_black_bot(bots: Res<Bots>, opponent: Res<Opponent>) -> bool {
+    bots.black.is_some() && *opponent == Opponent::External
+}
+
+fn hand_black_to_bot(bots: Res<Bots>, mut opponent: ResMut<Opponent>) {
+    if bots.black.is_some() {
+        *opponent = Opponent::External;
+    }
+}
+
+/// Plays white's whole turn at once once the bot answers, then passes. Answers arriving after
+/// white's turn ended are dropped.
+fn drive_white(
+    mut commands: Commands,
+    mut chessgrid: ResMut<ChessGrid>,
+    mut pass_requested: MessageWriter<PassRequested>,
+    mut delay: Local<Option<Timer>>,
+    mut bots: ResMut<Bots>,
+    active: Res<ActiveSide>,
+    turns: Res<TurnsStat>,
+    budget: Res<ActionBudget>,
+    time: Res<Time>,
+    pieces: Query<&Piece>,
+    children: Query<&Children>,
+) {
+    let Some(bot) = &mut bots.white else {
+        return;
+    };
+
+    if let Some(moves) = bot.poll() {
+        if active.0 == PieceColor::White {
+            for (from, to) in moves {
+                move_piece_entity(&mut commands, &mut chessgrid, &children, from, to);
+            }
+
+            pass_requested.write(PassRequested);
+        }
+        return;
+    }
+
+    if bot.waiting || active.0 != PieceColor::White {
+        return;
+    }
+
+    let timer = delay.get_or_insert_with(|| Timer::from_seconds(WHITE_BOT_DELAY, TimerMode::Once));
+    if !timer.tick(time.delta()).is_finished() {
+        return;
+    }
+    timer.reset();
+
+    let board = chessgrid.to_board(|entity| pieces.get(entity).ok().copied());
+    bot.request(board, PieceColor::White, turns.0.min(budget.0));
+}
+
+/// Hands black's turn to the bot once white passes, waiting with the board locked until it
+/// answers, the same way as for a player over the network.
+fn drive_black(
+    mut commands: Commands,
+    mut chessgrid: ResMut<ChessGrid>,
+    mut pass_requested: MessageWriter<PassRequested>,
+    mut board_state: ResMut<NextState<BoardState>>,
+    mut bots: ResMut<Bots>,
+    state: Res<State<BoardState>>,
+    active: Res<ActiveSide>,
+    pieces: Query<&Piece>,
+    children: Query<&Children>,
+) {
+    let Some(bot) = &mut bots.black else {
+        return;
+    };
+
+    match (active.0, state.get()) {
+        (PieceColor::White, BoardState::Waiting) => board_state.set(BoardState::Playing),
+        (PieceColor::Black, BoardState::Playing) => {
+            let board = chessgrid.to_board(|entity| pieces.get(entity).ok().copied());
+            bot.request(board, PieceColor::Black, TURNS_PER_PASS);
+            board_state.set(BoardState::Waiting);
+        }
+        (PieceColor::Black, BoardState::Waiting) => {
+            if let Some(moves) = bot.poll() {
+                for (from, to) in moves {
+                    move_piece_entity(&mut commands, &mut chessgrid, &children, from, to);
+                }
+
+                pass_requested.write(PassRequested);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Drops answers still on their way for the game that just ended.
+fn forget_requests(mut bots: ResMut<Bots>) {
+    let bots = &mut *bots;
+    bots.white
+        .iter_mut()
+        .chain(&mut bots.black)
+        .for_each(Bot::forget);
+}
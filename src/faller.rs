@@ -9,15 +9,55 @@ use std::f32::consts::{FRAC_PI_3, PI};
 const FALLER_SPEED: f32 = 100.0;
 const FALLER_ROTATION: f32 = 2.0 * FRAC_PI_3;
 const FALLER_HALF: f32 = 100.0;
+const DROP_SPEED: f32 = 8.0 * FALLER_SPEED;
+const DROP_ROTATION: f32 = 4.0 * FALLER_ROTATION;
+/// How far above its square a [`DropIn`] piece starts.
+pub const DROP_HEIGHT: f32 = 600.0;
+const DEBRIS_LAUNCH: f32 = 4.0 * FALLER_SPEED;
+const DEBRIS_GRAVITY: f32 = 3.0 * DROP_SPEED;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(AppState::Title), spawn_fallers);
-    app.add_systems(Update, update_fallers);
+    app.add_systems(Update, (update_fallers, update_drop_ins, update_debris));
 }
 
 #[derive(Component)]
 struct Faller;
 
+/// Drops a piece onto its square, spinning like the title screen fallers. Goes on the piece
+/// entity and moves the sprites sharing its square.
+#[derive(Component)]
+pub struct DropIn {
+    pub height: f32,
+}
+
+/// A sprite knocked off the board, flung sideways and falling while it spins like the title
+/// screen fallers.
+#[derive(Component)]
+pub struct Debris {
+    offset: Vec2,
+    velocity: Vec2,
+    angle: f32,
+    spin: f32,
+}
+
+impl Debris {
+    /// Flings the sprite left for a negative `direction` and right for a positive one.
+    pub fn new(direction: f32) -> Self {
+        let mut rng = rand::rng();
+
+        Self {
+            offset: Vec2::ZERO,
+            velocity: Vec2::new(
+                direction * rng.random_range(1.0..2.0) * FALLER_SPEED,
+                -DEBRIS_LAUNCH,
+            ),
+            angle: 0.0,
+            spin: direction * rng.random_range(0.5..1.0) * DROP_ROTATION,
+        }
+    }
+}
+
 fn spawn_fallers(
     mut commands: Commands,
     bg: Res<SpritesBgCollection>,
@@ -91,3 +131,51 @@ fn update_fallers(
         }
     }
 }
+
+fn update_drop_ins(
+    mut commands: Commands,
+    mut drops: Query<(Entity, &mut DropIn, &ChildOf)>,
+    mut transforms: Query<&mut UiTransform>,
+    children: Query<&Children>,
+    time: Res<Time>,
+) {
+    for (entity, mut drop, child_of) in &mut drops {
+        drop.height = (drop.height - DROP_SPEED * time.delta_secs()).max(0.0);
+
+        let transform = UiTransform {
+            translation: Val2::px(0.0, -drop.height),
+            rotation: Rot2::radians(drop.height / DROP_SPEED * DROP_ROTATION),
+            ..UiTransform::IDENTITY
+        };
+
+        if let Ok(siblings) = children.get(child_of.parent()) {
+            for sibling in siblings.iter() {
+                if let Ok(mut sibling_transform) = transforms.get_mut(sibling) {
+                    *sibling_transform = transform;
+                }
+            }
+        }
+
+        if drop.height == 0.0 {
+            commands.entity(entity).remove::<DropIn>();
+        }
+    }
+}
+
+fn update_debris(mut debris: Query<(&mut Debris, &mut UiTransform)>, time: Res<Time>) {
+    let dt = time.delta_secs();
+
+    for (mut debris, mut transform) in &mut debris {
+        debris.velocity.y += DEBRIS_GRAVITY * dt;
+        let velocity = debris.velocity;
+        debris.offset += velocity * dt;
+        let spin = debris.spin;
+        debris.angle += spin * dt;
+
+        *transform = UiTransform {
+            translation: Val2::px(debris.offset.x, debris.offset.y),
+            rotation: Rot2::radians(debris.angle),
+            ..UiTransform::IDENTITY
+        };
+    }
+}
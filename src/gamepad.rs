@@ -0,0 +1,110 @@
+//! Gamepad support. [`Actions`](crate::actions::Actions) reads pad buttons next to the keys, so
+//! every screen that reads actions also works from the couch, and prompts show pad buttons while a
+//! pad is in use.
+//!
+//! | Pad                  | Keyboard        |
+//! |----------------------|-----------------|
+//! | A / Cross            | Confirm, Enter  |
+//! | B / Circle           | Deselect        |
+//! | Y / Triangle         | Pass            |
+//! | X / Square           | Retry           |
+//! | D-Pad or left stick  | Arrows          |
+//! | RB / LB              | Tab / Shift+Tab |
+
+use crate::actions::Nav;
+use bevy::{
+    input::{InputSystems, keyboard::KeyboardInput, mouse::MouseButtonInput},
+    prelude::*,
+};
+
+/// How far the left stick has to lean before it moves the cursor.
+const STICK_THRESHOLD: f32 = 0.5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LastDevice>();
+    app.init_resource::<StickTap>();
+    app.add_systems(
+        PreUpdate,
+        (track_last_device, tap_stick).after(InputSystems),
+    );
+}
+
+/// Whichever of keyboard and mouse or gamepad was used last, deciding how prompts read.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LastDevice {
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+/// The way the left stick started leaning this frame, which counts as a press of that arrow.
+#[derive(Resource, Default)]
+pub struct StickTap(pub Option<Nav>);
+
+/// How a button reads in prompts, without brackets.
+pub fn glyph(button: GamepadButton) -> &'static str {
+    match button {
+        GamepadButton::South => "A",
+        GamepadButton::East => "B",
+        GamepadButton::North => "Y",
+        GamepadButton::West => "X",
+        GamepadButton::RightTrigger => "RB",
+        GamepadButton::LeftTrigger => "LB",
+        GamepadButton::RightTrigger2 => "RT",
+        GamepadButton::LeftTrigger2 => "LT",
+        GamepadButton::DPadUp => "UP",
+        GamepadButton::DPadDown => "DOWN",
+        GamepadButton::DPadLeft => "LEFT",
+        GamepadButton::DPadRight => "RIGHT",
+        GamepadButton::Start => "START",
+        GamepadButton::Select => "SELECT",
+        GamepadButton::LeftThumb => "LS",
+        GamepadButton::RightThumb => "RS",
+        _ => "?",
+    }
+}
+
+fn track_last_device(
+    mut last: ResMut<LastDevice>,
+    mut keys: MessageReader<KeyboardInput>,
+    mut mouse: MessageReader<MouseButtonInput>,
+    gamepads: Query<&Gamepad>,
+) {
+    if keys.read().count() + mouse.read().count() > 0 {
+        last.set_if_neq(LastDevice::Keyboard);
+    }
+
+    let stick_moved = |gamepad: &Gamepad| gamepad.left_stick().length() > STICK_THRESHOLD;
+
+    if gamepads
+        .iter()
+        .any(|gamepad| gamepad.get_just_pressed().next().is_some() || stick_moved(gamepad))
+    {
+        last.set_if_neq(LastDevice::Gamepad);
+    }
+}
+
+/// The stick taps an arrow each time it leans a new way.
+fn tap_stick(
+    mut tap: ResMut<StickTap>,
+    mut leaning: Local<Option<Nav>>,
+    gamepads: Query<&Gamepad>,
+) {
+    let stick = gamepads
+        .iter()
+        .map(Gamepad::left_stick)
+        .find(|stick| stick.length() > STICK_THRESHOLD);
+
+    let direction = stick.map(|stick| {
+        if stick.x.abs() > stick.y.abs() {
+            if stick.x > 0.0 { Nav::Right } else { Nav::Left }
+        } else if stick.y > 0.0 {
+            Nav::Up
+        } else {
+            Nav::Down
+        }
+    });
+
+    tap.0 = direction.filter(|_| direction != *leaning);
+    *leaning = direction;
+}
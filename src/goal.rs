@@ -0,0 +1,76 @@
+//! Progress towards the [`Goal`] of an authored board such as a puzzle or campaign level. Modes
+//! insert a [`GoalProgress`] when their board is set up, and the goal is checked after every pass.
+
+use crate::{
+    AppState, BoardState,
+    authored::{Goal, Outcome},
+    behaviour::*,
+    chessboard::TurnPassed,
+};
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnExit(AppState::Main), cleanup);
+    app.add_systems(
+        Update,
+        check_goal.run_if(in_state(AppState::Main).and(resource_exists::<GoalProgress>)),
+    );
+}
+
+#[derive(Resource, Debug)]
+pub struct GoalProgress {
+    pub goal: Goal,
+    pub starting_white: usize,
+    pub passes: u8,
+    pub outcome: Option<Outcome>,
+}
+
+impl GoalProgress {
+    pub fn new(goal: Goal, board: &Board) -> Self {
+        Self {
+            goal,
+            starting_white: board
+                .pieces()
+                .filter(|(_, piece)| piece.color == PieceColor::White)
+                .count(),
+            passes: 0,
+            outcome: None,
+        }
+    }
+}
+
+fn cleanup(mut commands: Commands) {
+    commands.remove_resource::<GoalProgress>();
+}
+
+fn check_goal(
+    mut turn_passed: MessageReader<TurnPassed>,
+    mut progress: ResMut<GoalProgress>,
+    mut board_state: ResMut<NextState<BoardState>>,
+    chessgrid: Res<ChessGrid>,
+    pieces: Query<&Piece>,
+) {
+    for _ in turn_passed.read() {
+        if progress.outcome.is_some() {
+            continue;
+        }
+
+        progress.passes += 1;
+
+        let remaining = chessgrid
+            .pieces
+            .iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entity| pieces.get(*entity).ok().copied());
+
+        progress.outcome =
+            progress
+                .goal
+                .outcome(progress.passes, progress.starting_white, remaining);
+
+        if progress.outcome.is_some() {
+            board_state.set(BoardState::Finished);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Hotseat mode. Two players share the machine, taking turns as white and black with the same
+//! action budget. A hand-off screen covers the board while they swap seats.
+
+use crate::{
+    AppState, BoardState, GameMode,
+    actions::{Action, Actions, PromptText},
+    ai::Opponent,
+    assets::FontsCollection,
+    behaviour::*,
+    chessboard::{ActiveSide, SelectedSquare, StartingLayout},
+    title::{TitleMode, mode_chosen},
+};
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        start_hotseat.run_if(in_state(AppState::Title).and(mode_chosen(TitleMode::Hotseat))),
+    );
+    app.add_systems(OnEnter(AppState::Main), setup.run_if(in_hotseat_mode));
+    app.add_systems(OnExit(AppState::Main), cleanup);
+    app.add_systems(
+        Update,
+        (check_kings, update_hotseat_text, hotseat_controls)
+            .chain()
+            .run_if(in_state(AppState::Main).and(in_two_player_mode)),
+    );
+    app.add_systems(OnEnter(BoardState::HandOff), spawn_hand_off);
+    app.add_systems(Update, take_seat.run_if(in_state(BoardState::HandOff)));
+}
+
+/// The side whose king is still standing once the other falls.
+#[derive(Resource)]
+pub struct Winner(pub PieceColor);
+
+#[derive(Component)]
+struct HotseatText;
+
+fn in_hotseat_mode(mode: Res<GameMode>) -> bool {
+    *mode == GameMode::Hotseat
+}
+
+/// Hotseat, LAN and mail games, which share the win check.
+fn in_two_player_mode(mode: Res<GameMode>) -> bool {
+    matches!(*mode, GameMode::Hotseat | GameMode::Lan | GameMode::Mail)
+}
+
+fn start_hotseat(
+    mut state: ResMut<NextState<AppState>>,
+    mut mode: ResMut<GameMode>,
+    mut layout: ResMut<StartingLayout>,
+    mut opponent: ResMut<Opponent>,
+) {
+    *mode = GameMode::Hotseat;
+    *layout = StartingLayout::default();
+    *opponent = Opponent::Human;
+    state.set(AppState::Main);
+}
+
+fn setup(mut commands: Commands, font: Res<FontsCollection>) {
+    commands.spawn((
+        Name::new("Hotseat Panel"),
+        DespawnOnExit(AppState::Main),
+        Node {
+            position_type: PositionType::Absolute,
+            left: px(10.0),
+            bottom: px(10.0),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Name::new("Hotseat Text"),
+            Text::default(),
+            HotseatText,
+            TextFont {
+                font: font.title.clone(),
+                font_size: 28.0,
+                ..default()
+            },
+        )],
+    ));
+}
+
+fn cleanup(mut commands: Commands) {
+    commands.remove_resource::<Winner>();
+}
+
+fn check_kings(
+    mut commands: Commands,
+    mut board_state: ResMut<NextState<BoardState>>,
+    state: Res<State<BoardState>>,
+    chessgrid: Res<ChessGrid>,
+    pieces: Query<&Piece>,
+) {
+    // Keeps checking until finished, since passing may have queued a hand-off this frame.
+    if *state.get() == BoardState::Finished {
+        return;
+    }
+
+    let kings: Vec<PieceColor> = chessgrid
+        .pieces
+        .iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entity| pieces.get(*entity).ok())
+        .filter(|piece| piece.kind == PieceKind::King)
+        .map(|piece| piece.color)
+        .collect();
+
+    if let [color] = kings[..] {
+        commands.insert_resource(Winner(color));
+        board_state.set(BoardState::Finished);
+    }
+}
+
+fn update_hotseat_text(
+    mut text_query: Query<&mut Text, With<HotseatText>>,
+    winner: Option<Res<Winner>>,
+    active: Res<ActiveSide>,
+    actions: Actions,
+) {
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+
+    text.0 = match winner {
+        Some(winner) => format!(
+            "{:?} wins!\nPress {} to return",
+            winner.0,
+            actions.prompt(Action::Confirm)
+        ),
+        None => format!("Hotseat\n{:?} is playing", active.0),
+    };
+}
+
+fn hotseat_controls(
+    mut state: ResMut<NextState<AppState>>,
+    actions: Actions,
+    winner: Option<Res<Winner>>,
+) {
+    if winner.is_some() && actions.just_pressed(Action::Confirm) {
+        state.set(AppState::Title);
+    }
+}
+
+fn spawn_hand_off(
+    mut commands: Commands,
+    font: Res<FontsCollection>,
+    active: Res<ActiveSide>,
+    selected: Query<Entity, With<SelectedSquare>>,
+) {
+    for entity in &selected {
+        commands.entity(entity).remove::<SelectedSquare>();
+    }
+
+    commands.spawn((
+        Name::new("Hand Off Screen"),
+        DespawnOnExit(BoardState::HandOff),
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100.0),
+            height: percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        BackgroundColor(Color::hsl(200.0, 0.9, 0.1)),
+        GlobalZIndex(100),
+        children![
+            (
+                Name::new("Hand Off Title"),
+                Text::new(format!("{:?} to move", active.0)),
+                TextFont {
+                    font: font.title.clone(),
+                    font_size: 70.0,
+                    ..default()
+                },
+            ),
+            (
+                Name::new("Hand Off Prompt"),
+                Text::default(),
+                PromptText(|actions| format!(
+                    "Pass the seat, then press {}",
+                    actions.prompt(Action::Confirm)
+                )),
+                TextFont {
+                    font: font.title.clone(),
+                    font_size: 28.0,
+                    ..default()
+                },
+            ),
+        ],
+    ));
+}
+
+fn take_seat(mut board_state: ResMut<NextState<BoardState>>, actions: Actions) {
+    if actions.just_pressed(Action::Confirm) {
+        board_state.set(BoardState::Playing);
+    }
+}
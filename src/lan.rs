@@ -0,0 +1,886 @@
+//! LAN multiplayer. One player hosts as white and the other joins as black, and they exchange
+//! [`NetMessage`]s over TCP, one RON value per line. The host is authoritative: it resolves every
+//! pass and sends the resulting board back, which the joining side adopts.
+//!
+//! To try it on one machine, start two instances, host in one and join `127.0.0.1:7878` in the
+//! other.
+
+use crate::{
+    AppState, BoardState, GameMode,
+    actions::{Action, Actions, Nav},
+    ai::Opponent,
+    assets::{FontsCollection, SpritesBgCollection, SpritesFgCollection},
+    behaviour::*,
+    chessboard::{
+        ActiveSide, PassRequested, PieceMoved, SidePassed, StartingLayout, move_piece_entity,
+        rebuild_board,
+    },
+    hotseat::Winner,
+    stats::{ActionBudget, TurnsStat},
+    title::{TitleMode, mode_chosen},
+};
+use bevy::{
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        Mutex,
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    thread,
+    time::Duration,
+};
+
+/// Bumped whenever [`NetMessage`] changes, so mismatched builds refuse each other.
+pub const PROTOCOL_VERSION: u32 = 2;
+const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        open_lobby.run_if(in_state(AppState::Title).and(mode_chosen(TitleMode::Lan))),
+    );
+    app.add_systems(OnEnter(AppState::Lobby), spawn_lobby);
+    app.add_systems(OnExit(AppState::Lobby), close_lobby);
+    app.add_systems(
+        Update,
+        (
+            type_address,
+            lobby_controls,
+            finish_joining,
+            accept_player,
+            handshake,
+            update_lobby_text,
+        )
+            .chain()
+            .run_if(in_state(AppState::Lobby)),
+    );
+
+    app.add_systems(OnEnter(AppState::Main), setup.run_if(in_lan_mode));
+    app.add_systems(OnEnter(AppState::Title), disconnect);
+    app.add_systems(
+        Update,
+        (
+            send_local_actions,
+            receive_remote_actions,
+            wait_for_remote,
+            update_lan_text,
+            lan_controls,
+        )
+            .chain()
+            .run_if(
+                in_state(AppState::Main)
+                    .and(in_lan_mode)
+                    .and(resource_exists::<Lan>),
+            ),
+    );
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum NetMessage {
+    /// First message from the joining side.
+    Hello {
+        version: u32,
+    },
+    /// The host accepts the player and the game starts.
+    Welcome {
+        version: u32,
+    },
+    /// The host refuses the player, e.g. for a protocol mismatch.
+    Reject {
+        reason: String,
+    },
+    Move {
+        from: [i32; 2],
+        to: [i32; 2],
+    },
+    /// The sender ends its turn. Only sent by the joining side, since the host resolves passes.
+    Pass,
+    /// The board after the host resolved a pass or refused a move, whose turn it is now and how
+    /// many moves that side has left.
+    Sync {
+        board: Box<Board>,
+        active: PieceColor,
+        turns: u8,
+    },
+}
+
+/// A TCP stream that sends and receives [`NetMessage`]s without blocking.
+struct Connection {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    /// Sent bytes the socket hasn't taken yet, written out by [`Connection::flush`].
+    outgoing: Vec<u8>,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self {
+            stream,
+            buffer: Vec::new(),
+            outgoing: Vec::new(),
+        })
+    }
+
+    fn send(&mut self, message: &NetMessage) -> io::Result<()> {
+        let mut line = ron::to_string(message).map_err(io::Error::other)?;
+        line.push('\n');
+        self.outgoing.extend_from_slice(line.as_bytes());
+        self.flush()
+    }
+
+    /// Writes as much of the queued bytes as the socket takes, leaving the rest for the next call.
+    fn flush(&mut self) -> io::Result<()> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.outgoing.drain(..written);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every complete message received since the last call.
+    fn receive(&mut self) -> io::Result<Vec<NetMessage>> {
+        let mut chunk = [0; 4096];
+
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(read) => self.buffer.extend_from_slice(&chunk[..read]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        let mut messages = Vec::new();
+
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            messages.push(ron::de::from_bytes(&line).map_err(io::Error::other)?);
+        }
+
+        Ok(messages)
+    }
+}
+
+#[derive(Resource)]
+struct Lobby {
+    address: String,
+    /// Whether joining is picked rather than hosting.
+    join: bool,
+    status: String,
+    listener: Option<TcpListener>,
+    /// A connection being opened on its own thread, as connecting can take a while.
+    joining: Option<Mutex<Receiver<io::Result<Connection>>>>,
+    pending: Option<Connection>,
+}
+
+/// The connection to the other player during a LAN game.
+#[derive(Resource)]
+pub struct Lan {
+    connection: Connection,
+    /// The side played on this machine. White hosts.
+    pub color: PieceColor,
+    pub disconnected: bool,
+}
+
+impl Lan {
+    fn is_host(&self) -> bool {
+        self.color == PieceColor::White
+    }
+
+    fn send(&mut self, message: &NetMessage) {
+        if let Err(err) = self.connection.send(message) {
+            warn!("lost connection: {err}");
+            self.disconnected = true;
+        }
+    }
+}
+
+#[derive(Component)]
+struct LobbyText;
+
+#[derive(Component)]
+struct LanText;
+
+fn in_lan_mode(mode: Res<GameMode>) -> bool {
+    *mode == GameMode::Lan
+}
+
+/// The host's answer to a [`NetMessage::Hello`], welcoming only players speaking its protocol.
+fn answer_hello(version: u32) -> NetMessage {
+    if version == PROTOCOL_VERSION {
+        NetMessage::Welcome {
+            version: PROTOCOL_VERSION,
+        }
+    } else {
+        NetMessage::Reject {
+            reason: format!("host speaks protocol {PROTOCOL_VERSION}, not {version}"),
+        }
+    }
+}
+
+fn to_coords([x, y]: [i32; 2]) -> Option<GridCoords> {
+    let coords = GridCoords::new(x, y);
+    coords.in_bounds().then_some(coords)
+}
+
+fn from_coords(coords: GridCoords) -> [i32; 2] {
+    [coords.0.x, coords.0.y]
+}
+
+fn open_lobby(mut state: ResMut<NextState<AppState>>) {
+    state.set(AppState::Lobby);
+}
+
+fn spawn_lobby(mut commands: Commands, font: Res<FontsCollection>) {
+    commands.insert_resource(Lobby {
+        address: DEFAULT_ADDRESS.to_string(),
+        join: false,
+        status: String::new(),
+        listener: None,
+        joining: None,
+        pending: None,
+    });
+
+    commands.spawn((
+        Name::new("Lobby Root"),
+        DespawnOnExit(AppState::Lobby),
+        Node {
+            width: percent(100.0),
+            height: percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            row_gap: px(20.0),
+            ..default()
+        },
+        children![
+            (
+                Name::new("Lobby Title"),
+                Text::new("LAN Game"),
+                TextFont {
+                    font: font.title.clone(),
+                    font_size: 70.0,
+                    ..default()
+                },
+            ),
+            (
+                Name::new("Lobby Text"),
+                Text::default(),
+                LobbyText,
+                TextLayout::new_with_justify(Justify::Center),
+                TextFont {
+                    font: font.title.clone(),
+                    font_size: 28.0,
+                    ..default()
+                },
+            ),
+        ],
+    ));
+}
+
+fn close_lobby(mut commands: Commands) {
+    commands.remove_resource::<Lobby>();
+}
+
+fn type_address(mut lobby: ResMut<Lobby>, mut keys: MessageReader<KeyboardInput>) {
+    for key in keys.read() {
+        if key.state != ButtonState::Pressed || lobby.listener.is_some() {
+            continue;
+        }
+
+        match &key.logical_key {
+            Key::Backspace => {
+                lobby.address.pop();
+            }
+            Key::Character(text)
+                if text
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || c == '.' || c == ':') =>
+            {
+                lobby.address.push_str(text);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn lobby_controls(
+    mut state: ResMut<NextState<AppState>>,
+    mut lobby: ResMut<Lobby>,
+    actions: Actions,
+) {
+    if actions.just_pressed(Action::Deselect) {
+        state.set(AppState::Title);
+        return;
+    }
+
+    if lobby.listener.is_some() || lobby.joining.is_some() || lobby.pending.is_some() {
+        return;
+    }
+
+    if [
+        Nav::Up,
+        Nav::Down,
+        Nav::Left,
+        Nav::Right,
+        Nav::Next,
+        Nav::Previous,
+    ]
+    .into_iter()
+    .any(|nav| actions.nav_just_pressed(nav))
+    {
+        lobby.join = !lobby.join;
+    }
+
+    if !actions.just_pressed(Action::Confirm) && !actions.nav_just_pressed(Nav::Select) {
+        return;
+    }
+
+    let Ok(address) = lobby.address.parse::<SocketAddr>() else {
+        lobby.status = format!("{:?} is not an address", lobby.address);
+        return;
+    };
+
+    if !lobby.join {
+        let result = TcpListener::bind(("0.0.0.0", address.port()))
+            .and_then(|listener| listener.set_nonblocking(true).map(|()| listener));
+
+        match result {
+            Ok(listener) => {
+                lobby.status = format!("Waiting for a player on port {}...", address.port());
+                lobby.listener = Some(listener);
+            }
+            Err(err) => lobby.status = format!("Could not host: {err}"),
+        }
+    } else {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let result = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+                .and_then(Connection::new)
+                .and_then(|mut connection| {
+                    connection.send(&NetMessage::Hello {
+                        version: PROTOCOL_VERSION,
+                    })?;
+                    Ok(connection)
+                });
+
+            let _ = sender.send(result);
+        });
+
+        lobby.status = format!("Connecting to {address}...");
+        lobby.joining = Some(Mutex::new(receiver));
+    }
+}
+
+fn finish_joining(mut lobby: ResMut<Lobby>) {
+    let Some(joining) = &mut lobby.joining else {
+        return;
+    };
+
+    let result = match joining.get_mut().unwrap().try_recv() {
+        Ok(result) => result,
+        Err(TryRecvError::Empty) => return,
+        Err(TryRecvError::Disconnected) => Err(io::Error::other("connecting stopped")),
+    };
+
+    lobby.joining = None;
+
+    match result {
+        Ok(connection) => {
+            lobby.status = format!("Joining {}...", lobby.address);
+            lobby.pending = Some(connection);
+        }
+        Err(err) => lobby.status = format!("Could not join: {err}"),
+    }
+}
+
+fn accept_player(mut lobby: ResMut<Lobby>) {
+    if lobby.pending.is_some() {
+        return;
+    }
+
+    let Some(listener) = &lobby.listener else {
+        return;
+    };
+
+    match listener
+        .accept()
+        .and_then(|(stream, _)| Connection::new(stream))
+    {
+        Ok(connection) => lobby.pending = Some(connection),
+        Err(err) if err.kind() == ErrorKind::WouldBlock => {}
+        Err(err) => lobby.status = format!("Could not accept a player: {err}"),
+    }
+}
+
+fn handshake(
+    mut commands: Commands,
+    mut state: ResMut<NextState<AppState>>,
+    mut mode: ResMut<GameMode>,
+    mut layout: ResMut<StartingLayout>,
+    mut opponent: ResMut<Opponent>,
+    mut lobby: ResMut<Lobby>,
+) {
+    let lobby = &mut *lobby;
+    let hosting = lobby.listener.is_some();
+
+    let Some(connection) = &mut lobby.pending else {
+        return;
+    };
+
+    let messages = match connection.flush().and_then(|()| connection.receive()) {
+        Ok(messages) => messages,
+        Err(err) => {
+            lobby.status = format!("Connection lost: {err}");
+            lobby.pending = None;
+            return;
+        }
+    };
+
+    for message in messages {
+        let color = match (hosting, message) {
+            (true, NetMessage::Hello { version }) => {
+                let answer = answer_hello(version);
+
+                if let Err(err) = connection.send(&answer) {
+                    lobby.status = format!("Connection lost: {err}");
+                    lobby.pending = None;
+                    return;
+                }
+
+                if let NetMessage::Reject { .. } = answer {
+                    lobby.status = format!("Turned away a player speaking protocol {version}");
+                    lobby.pending = None;
+                    return;
+                }
+
+                PieceColor::White
+            }
+            (false, NetMessage::Welcome { .. }) => PieceColor::Black,
+            (false, NetMessage::Reject { reason }) => {
+                lobby.status = format!("Rejected: {reason}");
+                lobby.pending = None;
+                return;
+            }
+            (_, message) => {
+                warn!("unexpected message in lobby: {message:?}");
+                continue;
+            }
+        };
+
+        let Some(connection) = lobby.pending.take() else {
+            return;
+        };
+
+        commands.insert_resource(Lan {
+            connection,
+            color,
+            disconnected: false,
+        });
+        *mode = GameMode::Lan;
+        *layout = StartingLayout::default();
+        *opponent = Opponent::Remote;
+        state.set(AppState::Main);
+        return;
+    }
+}
+
+fn update_lobby_text(
+    mut text_query: Query<&mut Text, With<LobbyText>>,
+    lobby: Res<Lobby>,
+    actions: Actions,
+) {
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+
+    let (host, join) = if lobby.join {
+        ("  Host as White  ", "> Join as Black <")
+    } else {
+        ("> Host as White <", "  Join as Black  ")
+    };
+
+    text.0 = format!(
+        "Address: {}\n{host}   {join}\n{} Choose   {} Back\n\n{}",
+        lobby.address,
+        actions.prompt(Action::Confirm),
+        actions.prompt(Action::Deselect),
+        lobby.status
+    );
+}
+
+fn setup(mut commands: Commands, font: Res<FontsCollection>) {
+    commands.spawn((
+        Name::new("LAN Panel"),
+        DespawnOnExit(AppState::Main),
+        Node {
+            position_type: PositionType::Absolute,
+            left: px(10.0),
+            bottom: px(10.0),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Name::new("LAN Text"),
+            Text::default(),
+            LanText,
+            TextFont {
+                font: font.title.clone(),
+                font_size: 28.0,
+                ..default()
+            },
+        )],
+    ));
+}
+
+fn disconnect(mut commands: Commands) {
+    commands.remove_resource::<Lan>();
+}
+
+fn send_local_actions(
+    mut lan: ResMut<Lan>,
+    mut moved: MessageReader<PieceMoved>,
+    mut side_passed: MessageReader<SidePassed>,
+    active: Res<ActiveSide>,
+    budget: Res<ActionBudget>,
+    chessgrid: Res<ChessGrid>,
+    pieces: Query<&Piece>,
+) {
+    for PieceMoved { from, to } in moved.read() {
+        lan.send(&NetMessage::Move {
+            from: from_coords(*from),
+            to: from_coords(*to),
+        });
+    }
+
+    for SidePassed(side) in side_passed.read() {
+        if lan.is_host() {
+            let board = chessgrid.to_board(|entity| pieces.get(entity).ok().copied());
+            lan.send(&NetMessage::Sync {
+                board: Box::new(board),
+                active: active.0,
+                turns: budget.0,
+            });
+        } else if *side == lan.color {
+            lan.send(&NetMessage::Pass);
+        }
+    }
+}
+
+fn receive_remote_actions(
+    mut commands: Commands,
+    mut lan: ResMut<Lan>,
+    mut chessgrid: ResMut<ChessGrid>,
+    mut active: ResMut<ActiveSide>,
+    mut turns: ResMut<TurnsStat>,
+    mut pass: MessageWriter<PassRequested>,
+    pieces: Query<&Piece>,
+    children: Query<&Children>,
+    fg: Res<SpritesFgCollection>,
+    bg: Res<SpritesBgCollection>,
+    font: Res<FontsCollection>,
+) {
+    if lan.disconnected {
+        return;
+    }
+
+    let messages = match lan
+        .connection
+        .flush()
+        .and_then(|()| lan.connection.receive())
+    {
+        Ok(messages) => messages,
+        Err(err) => {
+            warn!("lost connection: {err}");
+            lan.disconnected = true;
+            return;
+        }
+    };
+
+    let remote = lan.color.opponent();
+    let mut refused = false;
+
+    for message in messages {
+        match message {
+            NetMessage::Move { from, to } => {
+                let (Some(from), Some(to)) = (to_coords(from), to_coords(to)) else {
+                    continue;
+                };
+
+                let piece = chessgrid
+                    .get_piece(from)
+                    .and_then(|entity| pieces.get(entity).ok());
+
+                // The host only takes moves it would have allowed within the action budget. The
+                // joining side takes whatever the host sends.
+                let legal = piece.is_some_and(|piece| {
+                    piece.color == remote && legal_moves(*piece, from, *chessgrid).contains(&to)
+                });
+
+                if active.0 == remote && (!lan.is_host() || legal && turns.0 > 0) {
+                    move_piece_entity(&mut commands, &mut chessgrid, &children, from, to);
+                    turns.0 = turns.0.saturating_sub(1);
+                } else {
+                    warn!("refused move {from}-{to} from the other player");
+                    refused = true;
+                }
+            }
+            NetMessage::Pass if lan.is_host() && active.0 == remote => {
+                pass.write(PassRequested);
+            }
+            NetMessage::Sync {
+                board,
+                active: synced,
+                turns: synced_turns,
+            } if !lan.is_host() => {
+                let local = chessgrid.to_board(|entity| pieces.get(entity).ok().copied());
+
+                if local != *board {
+                    rebuild_board(
+                        &mut commands,
+                        &mut chessgrid,
+                        &children,
+                        &board,
+                        &fg,
+                        &bg,
+                        &font,
+                    );
+                }
+
+                active.0 = synced;
+                turns.0 = synced_turns;
+            }
+            message => warn!("unexpected message: {message:?}"),
+        }
+    }
+
+    // The other side already shows the refused move, so the host sends its own board back.
+    if refused && lan.is_host() {
+        let board = chessgrid.to_board(|entity| pieces.get(entity).ok().copied());
+        lan.send(&NetMessage::Sync {
+            board: Box::new(board),
+            active: active.0,
+            turns: turns.0,
+        });
+    }
+}
+
+fn wait_for_remote(
+    mut board_state: ResMut<NextState<BoardState>>,
+    state: Res<State<BoardState>>,
+    active: Res<ActiveSide>,
+    lan: Res<Lan>,
+    winner: Option<Res<Winner>>,
+) {
+    if lan.disconnected {
+        if *state.get() != BoardState::Finished {
+            board_state.set(BoardState::Finished);
+        }
+        return;
+    }
+
+    if winner.is_some() {
+        return;
+    }
+
+    if matches!(state.get(), BoardState::Playing | BoardState::Waiting) {
+        let next = if active.0 == lan.color {
+            BoardState::Playing
+        } else {
+            BoardState::Waiting
+        };
+
+        if *state.get() != next {
+            board_state.set(next);
+        }
+    }
+}
+
+fn update_lan_text(
+    mut text_query: Query<&mut Text, With<LanText>>,
+    lan: Res<Lan>,
+    active: Res<ActiveSide>,
+    winner: Option<Res<Winner>>,
+    actions: Actions,
+) {
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+
+    let confirm = actions.prompt(Action::Confirm);
+
+    text.0 = if lan.disconnected {
+        format!("Connection lost\nPress {confirm} to return")
+    } else if let Some(winner) = winner {
+        format!("{:?} wins!\nPress {confirm} to return", winner.0)
+    } else if active.0 == lan.color {
+        format!("You are {:?}\nYour turn", lan.color)
+    } else {
+        format!("You are {:?}\nWaiting for {:?}...", lan.color, active.0)
+    };
+}
+
+fn lan_controls(mut state: ResMut<NextState<AppState>>, actions: Actions, lan: Res<Lan>) {
+    if lan.disconnected && actions.just_pressed(Action::Confirm) {
+        state.set(AppState::Title);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// Both ends of a connection over localhost, the host's first.
+    fn connect() -> (Connection, Connection) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let joining = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (hosting, _) = listener.accept().unwrap();
+
+        (
+            Connection::new(hosting).unwrap(),
+            Connection::new(joining).unwrap(),
+        )
+    }
+
+    /// Polls `connection` the way the game does each frame until `count` messages arrived.
+    fn receive(connection: &mut Connection, count: usize) -> Vec<NetMessage> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut messages = Vec::new();
+
+        while messages.len() < count {
+            assert!(Instant::now() < deadline, "only received {messages:?}");
+            messages.extend(connection.receive().unwrap());
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        messages
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let (mut host, mut joiner) = connect();
+
+        let sent = [
+            NetMessage::Hello {
+                version: PROTOCOL_VERSION,
+            },
+            NetMessage::Move {
+                from: [4, 1],
+                to: [4, 2],
+            },
+            NetMessage::Pass,
+        ];
+        for message in &sent {
+            joiner.send(message).unwrap();
+        }
+        assert_eq!(receive(&mut host, sent.len()), sent);
+
+        let mut board = StartingLayout::default().0;
+        board.get_mut(GridCoords::new(4, 6)).unwrap().health = 12.5;
+
+        let sync = NetMessage::Sync {
+            board: Box::new(board),
+            active: PieceColor::Black,
+            turns: 2,
+        };
+        host.send(&sync).unwrap();
+        assert_eq!(receive(&mut joiner, 1), [sync]);
+    }
+
+    #[test]
+    fn messages_the_socket_cannot_take_yet_are_queued() {
+        let (mut host, mut joiner) = connect();
+        let sync = NetMessage::Sync {
+            board: Box::new(StartingLayout::default().0),
+            active: PieceColor::White,
+            turns: 3,
+        };
+
+        // Far more than the socket buffers hold while nothing is read on the other end.
+        let count = 2000;
+        for _ in 0..count {
+            host.send(&sync).unwrap();
+        }
+        assert!(!host.outgoing.is_empty());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut received = Vec::new();
+
+        while received.len() < count {
+            assert!(
+                Instant::now() < deadline,
+                "only received {}",
+                received.len()
+            );
+            host.flush().unwrap();
+            received.extend(joiner.receive().unwrap());
+        }
+
+        assert!(received.iter().all(|message| *message == sync));
+        assert!(host.outgoing.is_empty());
+    }
+
+    #[test]
+    fn hosts_turn_away_other_protocol_versions() {
+        assert_eq!(
+            answer_hello(PROTOCOL_VERSION),
+            NetMessage::Welcome {
+                version: PROTOCOL_VERSION
+            }
+        );
+
+        let (mut host, mut joiner) = connect();
+
+        joiner
+            .send(&NetMessage::Hello {
+                version: PROTOCOL_VERSION + 1,
+            })
+            .unwrap();
+
+        let [NetMessage::Hello { version }] = receive(&mut host, 1)[..] else {
+            panic!("expected a hello");
+        };
+        host.send(&answer_hello(version)).unwrap();
+
+        let reply = receive(&mut joiner, 1);
+        assert!(
+            matches!(&reply[..], [NetMessage::Reject { reason }] if reason.contains("protocol")),
+            "{reply:?}"
+        );
+    }
+
+    #[test]
+    fn closed_connections_report_an_error() {
+        let (mut host, joiner) = connect();
+        drop(joiner);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while host.receive().is_ok() {
+            assert!(Instant::now() < deadline);
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
@@ -0,0 +1,9 @@
+//! The rules of the game, shared by the game and the headless `tournament`, `tui` and `solve`
+//! binaries.
+
+pub mod ai;
+pub mod authored;
+pub mod behaviour;
+pub mod bot;
+pub mod solver;
+pub mod stats;
@@ -9,7 +9,9 @@ pub(super) fn plugin(app: &mut App) {
             .load_collection::<FontsCollection>()
             .load_collection::<SpritesFgCollection>()
             .load_collection::<SpritesBgCollection>()
-            .load_collection::<SoundsCollection>(),
+            .load_collection::<SoundsCollection>()
+            .load_collection::<PuzzlesCollection>()
+            .load_collection::<CampaignCollection>(),
     );
 
     app.add_systems(OnEnter(AppState::Loading), spawn_loading_screen);
@@ -0,0 +1,539 @@
+//! Play by mail. Each player keeps their side of the game in the save directory and the two
+//! exchange a [`TurnFile`] per turn, by mail or any other means: a played turn is written to
+//! `mail/outbox`, and the opponent's reply is picked up from `mail/inbox`.
+//!
+//! Whoever starts first plays white. Starting with white's first turn file already in the inbox
+//! joins that game as black instead. Turn files name the position they were played from by its
+//! hash and carry the hash of the position they lead to, and importing replays the moves, so files
+//! for another game, out of order or edited by hand are refused.
+
+use crate::{
+    AppState, BoardState, GameMode,
+    actions::{Action, Actions},
+    ai::Opponent,
+    assets::{FontsCollection, SpritesBgCollection, SpritesFgCollection},
+    behaviour::{Move, *},
+    chessboard::{ActiveSide, PieceMoved, SidePassed, StartingLayout, rebuild_board},
+    hotseat::Winner,
+    save,
+    stats::ActionBudget,
+    title::{TitleMode, mode_chosen},
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever [`TurnFile`] changes, so files from mismatched builds are refused.
+pub const MAIL_VERSION: u32 = 1;
+const GAME_FILE: &str = "mail/game.ron";
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        start_mail.run_if(in_state(AppState::Title).and(mode_chosen(TitleMode::Mail))),
+    );
+    app.add_systems(OnEnter(AppState::Main), setup.run_if(in_mail_mode));
+    app.add_systems(OnEnter(AppState::Title), close_mail);
+    app.add_systems(
+        Update,
+        (
+            record_moves,
+            send_turn,
+            import_turn,
+            wait_for_turn,
+            finish_game,
+            update_mail_text,
+            mail_controls,
+        )
+            .chain()
+            .run_if(
+                in_state(AppState::Main)
+                    .and(in_mail_mode)
+                    .and(resource_exists::<Mail>),
+            ),
+    );
+}
+
+/// One side's turn: its moves and the positions before and after its attack phase.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TurnFile {
+    pub version: u32,
+    /// 1 for white's first turn.
+    pub turn: u32,
+    pub color: PieceColor,
+    /// [`state_hash`] of the position the turn was played from.
+    pub base: u64,
+    pub moves: Vec<([i32; 2], [i32; 2])>,
+    /// [`state_hash`] of the position after the turn's attack phase.
+    pub hash: u64,
+}
+
+/// The local side of a game, saved after every turn.
+#[derive(Serialize, Deserialize, Debug)]
+struct MailGame {
+    color: PieceColor,
+    /// Turns played so far by both sides.
+    turn: u32,
+    board: Board,
+}
+
+impl MailGame {
+    fn side_to_move(&self) -> PieceColor {
+        if self.turn.is_multiple_of(2) {
+            PieceColor::White
+        } else {
+            PieceColor::Black
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Mail {
+    game: MailGame,
+    /// Moves made so far this turn.
+    moves: Vec<Move>,
+    status: String,
+    finished: bool,
+}
+
+#[derive(Component)]
+struct MailText;
+
+/// Hashes the position after `turn` turns with FNV-1a over its RON form, which stays the same
+/// across machines and builds.
+pub fn state_hash(turn: u32, board: &Board) -> u64 {
+    let text = ron::to_string(&(turn, board)).unwrap_or_default();
+
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn turn_file_name(folder: &str, turn: u32) -> String {
+    format!("mail/{folder}/turn_{turn:03}.ron")
+}
+
+fn display_path(name: &str) -> String {
+    save::path(name).map_or(name.to_string(), |path| path.display().to_string())
+}
+
+fn to_coords([x, y]: [i32; 2]) -> Option<GridCoords> {
+    let coords = GridCoords::new(x, y);
+    coords.in_bounds().then_some(coords)
+}
+
+/// Plays `file` on top of `game`, returning the resulting position or why the file was refused.
+fn replay(game: &MailGame, file: &TurnFile, budget: u8) -> Result<Board, String> {
+    if file.version != MAIL_VERSION {
+        return Err(format!(
+            "turn file is version {}, not {MAIL_VERSION}",
+            file.version
+        ));
+    }
+
+    if file.turn != game.turn + 1 || file.color != game.side_to_move() {
+        return Err(format!(
+            "expected {:?}'s turn {}, got {:?}'s turn {}",
+            game.side_to_move(),
+            game.turn + 1,
+            file.color,
+            file.turn
+        ));
+    }
+
+    if file.base != state_hash(game.turn, &game.board) {
+        return Err("turn file was played from a different position".to_string());
+    }
+
+    if file.moves.len() > budget as usize {
+        return Err(format!("turn file has more than {budget} moves"));
+    }
+
+    let mut board = game.board;
+
+    for (from, to) in &file.moves {
+        let (Some(from), Some(to)) = (to_coords(*from), to_coords(*to)) else {
+            return Err("turn file has a move off the board".to_string());
+        };
+
+        let legal = board.get(from).is_some_and(|piece| {
+            piece.color == file.color && legal_moves(piece, from, board).contains(&to)
+        });
+
+        if !legal {
+            return Err(format!("turn file has an illegal move {from}{to}"));
+        }
+
+        board.move_piece(from, to);
+    }
+
+    board.apply_damage_with_xp(file.color);
+
+    if file.hash != state_hash(file.turn, &board) {
+        return Err("turn file has been tampered with".to_string());
+    }
+
+    Ok(board)
+}
+
+fn in_mail_mode(mode: Res<GameMode>) -> bool {
+    *mode == GameMode::Mail
+}
+
+fn start_mail(
+    mut commands: Commands,
+    mut state: ResMut<NextState<AppState>>,
+    mut mode: ResMut<GameMode>,
+    mut layout: ResMut<StartingLayout>,
+    mut opponent: ResMut<Opponent>,
+) {
+    let game = save::load::<MailGame>(GAME_FILE).unwrap_or_else(|| {
+        let joining = save::load::<TurnFile>(&turn_file_name("inbox", 1)).is_some();
+
+        MailGame {
+            color: if joining {
+                PieceColor::Black
+            } else {
+                PieceColor::White
+            },
+            turn: 0,
+            board: StartingLayout::default().0,
+        }
+    });
+
+    *mode = GameMode::Mail;
+    *layout = StartingLayout(game.board);
+    *opponent = Opponent::Remote;
+    commands.insert_resource(Mail {
+        game,
+        moves: Vec::new(),
+        status: String::new(),
+        finished: false,
+    });
+    state.set(AppState::Main);
+}
+
+fn close_mail(mut commands: Commands) {
+    commands.remove_resource::<Mail>();
+}
+
+fn setup(mut commands: Commands, font: Res<FontsCollection>) {
+    commands.spawn((
+        Name::new("Mail Panel"),
+        DespawnOnExit(AppState::Main),
+        Node {
+            position_type: PositionType::Absolute,
+            left: px(10.0),
+            bottom: px(10.0),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Name::new("Mail Text"),
+            Text::default(),
+            MailText,
+            TextFont {
+                font: font.title.clone(),
+                font_size: 28.0,
+                ..default()
+            },
+        )],
+    ));
+}
+
+fn record_moves(mut mail: ResMut<Mail>, mut moved: MessageReader<PieceMoved>) {
+    for PieceMoved { from, to } in moved.read() {
+        mail.moves.push((*from, *to));
+    }
+}
+
+/// Writes the local turn to the outbox once it has been passed.
+fn send_turn(mut mail: ResMut<Mail>, mut side_passed: MessageReader<SidePassed>) {
+    for SidePassed(side) in side_passed.read() {
+        if *side != mail.game.color {
+            continue;
+        }
+
+        let moves = std::mem::take(&mut mail.moves);
+        let mut board = mail.game.board;
+
+        for (from, to) in &moves {
+            board.move_piece(*from, *to);
+        }
+        board.apply_damage_with_xp(*side);
+
+        let file = TurnFile {
+            version: MAIL_VERSION,
+            turn: mail.game.turn + 1,
+            color: *side,
+            base: state_hash(mail.game.turn, &mail.game.board),
+            moves: moves
+                .iter()
+                .map(|(from, to)| ([from.0.x, from.0.y], [to.0.x, to.0.y]))
+                .collect(),
+            hash: state_hash(mail.game.turn + 1, &board),
+        };
+
+        let name = turn_file_name("outbox", file.turn);
+        save::store(&name, &file);
+
+        mail.game.turn = file.turn;
+        mail.game.board = board;
+        save::store(GAME_FILE, &mail.game);
+
+        mail.status = format!("Send {} to your opponent", display_path(&name));
+    }
+}
+
+/// Picks up the opponent's reply from the inbox when asked.
+fn import_turn(
+    mut commands: Commands,
+    mut mail: ResMut<Mail>,
+    mut chessgrid: ResMut<ChessGrid>,
+    actions: Actions,
+    budget: Res<ActionBudget>,
+    children: Query<&Children>,
+    fg: Res<SpritesFgCollection>,
+    bg: Res<SpritesBgCollection>,
+    font: Res<FontsCollection>,
+) {
+    if mail.finished
+        || mail.game.side_to_move() == mail.game.color
+        || !actions.just_pressed(Action::Confirm)
+    {
+        return;
+    }
+
+    let name = turn_file_name("inbox", mail.game.turn + 1);
+    let Some(file) = save::load::<TurnFile>(&name) else {
+        mail.status = format!("No readable turn file at {}", display_path(&name));
+        return;
+    };
+
+    match replay(&mail.game, &file, budget.0) {
+        Ok(board) => {
+            rebuild_board(
+                &mut commands,
+                &mut chessgrid,
+                &children,
+                &board,
+                &fg,
+                &bg,
+                &font,
+            );
+
+            mail.game.turn = file.turn;
+            mail.game.board = board;
+            save::store(GAME_FILE, &mail.game);
+            mail.status = format!("Imported turn {}", file.turn);
+        }
+        Err(reason) => {
+            warn!("refused {}: {reason}", display_path(&name));
+            mail.status = format!("Refused: {reason}");
+        }
+    }
+}
+
+fn wait_for_turn(
+    mut board_state: ResMut<NextState<BoardState>>,
+    mut active: ResMut<ActiveSide>,
+    mut side_passed: MessageReader<SidePassed>,
+    state: Res<State<BoardState>>,
+    mail: Res<Mail>,
+    winner: Option<Res<Winner>>,
+) {
+    // A pass not yet written out would hand the turn straight back.
+    if winner.is_some() || !side_passed.is_empty() {
+        side_passed.clear();
+        return;
+    }
+
+    let side = mail.game.side_to_move();
+    if active.0 != side {
+        active.0 = side;
+    }
+
+    if matches!(state.get(), BoardState::Playing | BoardState::Waiting) {
+        let next = if side == mail.game.color {
+            BoardState::Playing
+        } else {
+            BoardState::Waiting
+        };
+
+        if *state.get() != next {
+            board_state.set(next);
+        }
+    }
+}
+
+/// Forgets a finished game, so the next one starts fresh.
+fn finish_game(mut mail: ResMut<Mail>, winner: Option<Res<Winner>>) {
+    if winner.is_some() && !mail.finished {
+        mail.finished = true;
+        save::remove(GAME_FILE);
+    }
+}
+
+fn update_mail_text(
+    mut text_query: Query<&mut Text, With<MailText>>,
+    mail: Res<Mail>,
+    winner: Option<Res<Winner>>,
+    actions: Actions,
+) {
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+
+    let color = mail.game.color;
+    let turn = mail.game.turn + 1;
+
+    let prompt = if let Some(winner) = winner {
+        format!(
+            "{:?} wins!\nPress {} to return",
+            winner.0,
+            actions.prompt(Action::Confirm)
+        )
+    } else if mail.game.side_to_move() == color {
+        format!(
+            "You are {color:?}\nTurn {turn}: move, then press {} to send",
+            actions.prompt(Action::Pass)
+        )
+    } else {
+        format!(
+            "You are {color:?}\nWaiting for turn {turn}\nPut it in {} and press {}\n{} to leave, the game is saved",
+            display_path(&turn_file_name("inbox", turn)),
+            actions.prompt(Action::Confirm),
+            actions.prompt(Action::Deselect)
+        )
+    };
+
+    text.0 = format!("{prompt}\n{}", mail.status);
+}
+
+/// The game is saved after every turn, so a player waiting for a reply can leave at any time.
+fn mail_controls(
+    mut state: ResMut<NextState<AppState>>,
+    board_state: Res<State<BoardState>>,
+    actions: Actions,
+) {
+    if *board_state.get() == BoardState::Waiting && actions.just_pressed(Action::Deselect) {
+        state.set(AppState::Title);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUDGET: u8 = 3;
+
+    fn square(name: &str) -> GridCoords {
+        name.parse().unwrap()
+    }
+
+    fn new_game() -> MailGame {
+        MailGame {
+            color: PieceColor::Black,
+            turn: 0,
+            board: StartingLayout::default().0,
+        }
+    }
+
+    /// The turn file `send_turn` would write for `moves` played on `game`.
+    fn turn_file(game: &MailGame, moves: &[(&str, &str)]) -> TurnFile {
+        let moves: Vec<Move> = moves
+            .iter()
+            .map(|(from, to)| (square(from), square(to)))
+            .collect();
+
+        let mut board = game.board;
+        for (from, to) in &moves {
+            board.move_piece(*from, *to);
+        }
+        board.apply_damage_with_xp(game.side_to_move());
+
+        TurnFile {
+            version: MAIL_VERSION,
+            turn: game.turn + 1,
+            color: game.side_to_move(),
+            base: state_hash(game.turn, &game.board),
+            moves: moves
+                .iter()
+                .map(|(from, to)| ([from.0.x, from.0.y], [to.0.x, to.0.y]))
+                .collect(),
+            hash: state_hash(game.turn + 1, &board),
+        }
+    }
+
+    fn refusal(file: &TurnFile) -> String {
+        replay(&new_game(), file, BUDGET).unwrap_err()
+    }
+
+    #[test]
+    fn replays_a_valid_turn() {
+        let game = new_game();
+        let file = turn_file(&game, &[("e2", "e3"), ("d2", "d3")]);
+
+        let board = replay(&game, &file, BUDGET).unwrap();
+
+        assert!(board.get(square("e2")).is_none());
+        assert_eq!(board.get(square("e3")).unwrap().kind, PieceKind::Pawn);
+        assert_eq!(state_hash(1, &board), file.hash);
+    }
+
+    #[test]
+    fn refuses_another_version() {
+        let mut file = turn_file(&new_game(), &[("e2", "e3")]);
+        file.version = MAIL_VERSION + 1;
+
+        assert!(refusal(&file).contains("version"));
+    }
+
+    #[test]
+    fn refuses_turns_out_of_order() {
+        let mut file = turn_file(&new_game(), &[("e2", "e3")]);
+        file.turn = 2;
+
+        assert!(refusal(&file).starts_with("expected"));
+
+        let mut file = turn_file(&new_game(), &[("e2", "e3")]);
+        file.color = PieceColor::Black;
+
+        assert!(refusal(&file).starts_with("expected"));
+    }
+
+    #[test]
+    fn refuses_another_base_position() {
+        let mut file = turn_file(&new_game(), &[("e2", "e3")]);
+        file.base ^= 1;
+
+        assert!(refusal(&file).contains("different position"));
+    }
+
+    #[test]
+    fn refuses_illegal_moves() {
+        // A pawn can't move two squares, and black pieces aren't white's to move.
+        for moves in [[("e2", "e4")], [("e7", "e6")]] {
+            let file = turn_file(&new_game(), &moves);
+            assert!(refusal(&file).contains("illegal move"));
+        }
+    }
+
+    #[test]
+    fn refuses_more_moves_than_the_budget() {
+        let file = turn_file(
+            &new_game(),
+            &[("a2", "a3"), ("b2", "b3"), ("c2", "c3"), ("d2", "d3")],
+        );
+
+        assert!(refusal(&file).contains("more than"));
+    }
+
+    #[test]
+    fn refuses_an_edited_hash() {
+        let mut file = turn_file(&new_game(), &[("e2", "e3")]);
+        file.hash ^= 1;
+
+        assert!(refusal(&file).contains("tampered"));
+    }
+}
@@ -1,18 +1,33 @@
 #![cfg_attr(bevy_lint, feature(register_tool), register_tool(bevy))]
 #![cfg_attr(not(feature = "dev"), windows_subsystem = "windows")]
 
+mod actions;
 mod assets;
-mod behaviour;
 mod camera;
+mod campaign;
 mod chessboard;
 #[cfg(feature = "dev")]
 mod dev_tools;
+mod external;
 mod faller;
+mod gamepad;
+mod goal;
+mod hotseat;
+#[cfg(not(target_arch = "wasm32"))]
+mod lan;
 mod loading;
-mod stats;
+mod mail;
+mod pause;
+mod puzzle;
+mod run;
+mod save;
+mod settings;
+mod theme;
 mod title;
+mod waves;
 
 use bevy::{asset::AssetMetaCheck, prelude::*};
+use bevy_jam_7::{ai, authored, behaviour, bot, stats};
 use bevy_seedling::SeedlingPlugin;
 use rand::prelude::*;
 
@@ -43,16 +58,36 @@ impl Plugin for AppPlugin {
         ));
 
         app.add_plugins((
+            actions::plugin,
+            ai::plugin,
             camera::plugin,
+            campaign::plugin,
             chessboard::plugin,
             #[cfg(feature = "dev")]
             dev_tools::plugin,
+            external::plugin,
             faller::plugin,
+            gamepad::plugin,
+            goal::plugin,
+            hotseat::plugin,
+            #[cfg(not(target_arch = "wasm32"))]
+            lan::plugin,
             loading::plugin,
+            mail::plugin,
+        ));
+        app.add_plugins((
+            pause::plugin,
+            puzzle::plugin,
+            run::plugin,
+            settings::plugin,
             title::plugin,
+            waves::plugin,
         ));
 
         app.init_state::<AppState>();
+        app.add_sub_state::<BoardState>();
+        app.add_sub_state::<PauseState>();
+        app.init_resource::<GameMode>();
         app.insert_resource(ClearColor(Color::hsl(200.0, 0.9, 0.1)));
         app.add_systems(Update, update_typewriters);
     }
@@ -63,9 +98,55 @@ pub enum AppState {
     #[default]
     Loading,
     Title,
+    LevelSelect,
+    /// Hosting or joining a LAN game.
+    Lobby,
     Main,
+    /// Picking a reward between battles of a run.
+    Rewards,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Hash, SubStates, Clone, Copy)]
+#[source(AppState = AppState::Main)]
+pub enum BoardState {
+    #[default]
+    Playing,
+    /// The queen is talking and the board waits.
+    Dialogue,
+    /// The board is covered while hotseat players swap.
+    HandOff,
+    /// The other player is moving over the network or by mail.
+    Waiting,
+    Finished,
+}
+
+/// Whether the pause menu is open over the board. Kept apart from [`BoardState`] so pausing
+/// leaves the game exactly as it was.
+#[derive(Debug, Default, PartialEq, Eq, Hash, SubStates, Clone, Copy)]
+#[source(AppState = AppState::Main)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+#[derive(Resource, Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum GameMode {
+    #[default]
+    Standard,
+    Puzzle(usize),
+    Campaign(usize),
+    Run,
+    Waves,
+    Hotseat,
+    Lan,
+    Mail,
 }
 
+pub const BUTTON: Color = Color::hsl(200.0, 1.0, 0.25);
+pub const BUTTON_HOVER: Color = Color::hsl(200.0, 1.0, 0.5);
+pub const BUTTON_LOCKED: Color = Color::hsl(200.0, 0.2, 0.2);
+
 #[derive(Component)]
 pub struct Typewriter {
     pub full_text: String,
@@ -77,11 +158,9 @@ fn update_typewriters(mut query: Query<(&mut Text, &mut Typewriter)>, time: Res<
     for (mut text, mut writer) in &mut query {
         writer.timer.tick(time.delta());
 
-        if writer.timer.just_finished() {
-            if writer.visible_chars < writer.full_text.len() {
-                writer.visible_chars += 1;
-                text.0 = writer.full_text[..writer.visible_chars].to_string();
-            }
+        if writer.timer.just_finished() && writer.visible_chars < writer.full_text.len() {
+            writer.visible_chars += 1;
+            text.0 = writer.full_text[..writer.visible_chars].to_string();
         }
     }
 }
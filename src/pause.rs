@@ -0,0 +1,427 @@
+//! The pause menu, opened with [`Action::Deselect`] when nothing is selected. Time stands still
+//! while it is open, so animations and timers pick up where they left off.
+
+use crate::{
+    AppState, BUTTON, BUTTON_HOVER, BUTTON_LOCKED, BoardState, GameMode, PauseState,
+    actions::{Action, Actions, BINDABLE, Bindings, Nav, PromptText},
+    assets::FontsCollection,
+    settings::Settings,
+    theme::Theme,
+};
+use bevy::prelude::*;
+
+/// Volumes the settings page cycles through.
+const VOLUMES: [f32; 5] = [1.0, 0.75, 0.5, 0.25, 0.0];
+
+/// Move animation lengths the settings page cycles through, with their names.
+const MOVE_SPEEDS: [(f32, &str); 4] =
+    [(0.6, "Slow"), (0.3, "Normal"), (0.15, "Fast"), (0.0, "Off")];
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_sub_state::<PausePage>();
+    app.init_resource::<MenuFocus>();
+    app.init_resource::<Rebinding>();
+    app.add_systems(OnEnter(PauseState::Paused), pause_time);
+    app.add_systems(OnExit(PauseState::Paused), resume_time);
+    app.add_systems(OnExit(AppState::Main), resume_time);
+    app.add_systems(OnEnter(PausePage::Menu), spawn_menu);
+    app.add_systems(OnEnter(PausePage::Settings), spawn_settings);
+    app.add_systems(OnEnter(PausePage::Controls), spawn_controls);
+    app.add_systems(OnExit(PausePage::Controls), stop_rebinding);
+    app.add_systems(
+        Update,
+        (
+            capture_binding,
+            navigate_menu.pipe(press_buttons).run_if(not_rebinding),
+            update_button_labels,
+            color_buttons,
+        )
+            .chain()
+            .run_if(in_state(PauseState::Paused)),
+    );
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Hash, SubStates, Clone, Copy)]
+#[source(PauseState = PauseState::Paused)]
+enum PausePage {
+    #[default]
+    Menu,
+    Settings,
+    Controls,
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum PauseButton {
+    Resume,
+    Restart,
+    Settings,
+    Title,
+    Quit,
+    Volume,
+    HealthBars,
+    MoveSpeed,
+    Theme,
+    Controls,
+    Bind(Action),
+    ResetBindings,
+    Back,
+}
+
+/// A button that cannot be pressed in the current game mode.
+#[derive(Component)]
+struct Locked;
+
+#[derive(Resource, Default)]
+struct MenuFocus(Option<usize>);
+
+/// Position of a button in its page, for moving the focus with the arrow keys.
+#[derive(Component)]
+struct MenuItem(usize);
+
+/// The action waiting for the next key press to be bound to it.
+#[derive(Resource, Default)]
+struct Rebinding(Option<Action>);
+
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn resume_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn spawn_page(
+    commands: &mut Commands,
+    font: &FontsCollection,
+    page: PausePage,
+    title: &str,
+    buttons: &[(PauseButton, bool)],
+) {
+    commands.insert_resource(MenuFocus::default());
+
+    commands
+        .spawn((
+            Name::new("Pause Menu"),
+            DespawnOnExit(page),
+            Node {
+                position_type: PositionType::Absolute,
+                width: percent(100.0),
+                height: percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: px(6.0),
+                ..default()
+            },
+            BackgroundColor(Color::hsla(0.0, 0.0, 0.0, 0.7)),
+            GlobalZIndex(80),
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Name::new("Pause Title"),
+                Text::new(title),
+                TextFont {
+                    font: font.title.clone(),
+                    font_size: 70.0,
+                    ..default()
+                },
+            ));
+
+            for (index, (button, unlocked)) in buttons.iter().enumerate() {
+                let mut entity = p.spawn((
+                    Name::new("Pause Button"),
+                    Button,
+                    *button,
+                    MenuItem(index),
+                    Node {
+                        width: px(400.0),
+                        // Keeps the controls page, the longest, within a 720 pixel window.
+                        padding: px(6.0).into(),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    Text::default(),
+                    TextFont {
+                        font: font.title.clone(),
+                        font_size: 28.0,
+                        ..default()
+                    },
+                    TextLayout::new_with_justify(Justify::Center),
+                    BackgroundColor(if *unlocked { BUTTON } else { BUTTON_LOCKED }),
+                ));
+
+                if !unlocked {
+                    entity.insert(Locked);
+                }
+            }
+
+            p.spawn((
+                Name::new("Pause Hint"),
+                Text::default(),
+                PromptText(hint),
+                TextFont {
+                    font: font.title.clone(),
+                    font_size: 20.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn hint(actions: &Actions) -> String {
+    format!("Press {} to go back", actions.prompt(Action::Deselect))
+}
+
+fn spawn_menu(mut commands: Commands, font: Res<FontsCollection>, mode: Res<GameMode>) {
+    // Network and mail games carry on from the shared position, so they cannot start over.
+    let can_restart = !matches!(*mode, GameMode::Lan | GameMode::Mail);
+
+    spawn_page(
+        &mut commands,
+        &font,
+        PausePage::Menu,
+        "Paused",
+        &[
+            (PauseButton::Resume, true),
+            (PauseButton::Restart, can_restart),
+            (PauseButton::Settings, true),
+            (PauseButton::Title, true),
+            (PauseButton::Quit, true),
+        ],
+    );
+}
+
+fn spawn_settings(mut commands: Commands, font: Res<FontsCollection>) {
+    spawn_page(
+        &mut commands,
+        &font,
+        PausePage::Settings,
+        "Settings",
+        &[
+            (PauseButton::Volume, true),
+            (PauseButton::HealthBars, true),
+            (PauseButton::MoveSpeed, true),
+            (PauseButton::Theme, true),
+            (PauseButton::Controls, true),
+            (PauseButton::Back, true),
+        ],
+    );
+}
+
+fn spawn_controls(mut commands: Commands, font: Res<FontsCollection>) {
+    let buttons: Vec<(PauseButton, bool)> = Action::ALL
+        .into_iter()
+        .map(|action| (PauseButton::Bind(action), true))
+        .chain([
+            (PauseButton::ResetBindings, true),
+            (PauseButton::Back, true),
+        ])
+        .collect();
+
+    spawn_page(
+        &mut commands,
+        &font,
+        PausePage::Controls,
+        "Controls",
+        &buttons,
+    );
+}
+
+fn stop_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+}
+
+fn not_rebinding(rebinding: Res<Rebinding>) -> bool {
+    rebinding.0.is_none()
+}
+
+/// Binds the next bindable key pressed, clearing the press so nothing else acts on it.
+fn capture_binding(
+    mut rebinding: ResMut<Rebinding>,
+    mut settings: ResMut<Settings>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+
+    let Some(key) = BINDABLE.into_iter().find(|key| keys.just_pressed(*key)) else {
+        return;
+    };
+
+    keys.clear_just_pressed(key);
+
+    if settings.bindings.bind(action, key) {
+        rebinding.0 = None;
+    }
+}
+
+/// Returns whether the focused button was pressed, for [`press_buttons`].
+fn navigate_menu(
+    mut focus: ResMut<MenuFocus>,
+    mut pause: ResMut<NextState<PauseState>>,
+    mut page_state: ResMut<NextState<PausePage>>,
+    page: Res<State<PausePage>>,
+    actions: Actions,
+    items: Query<&MenuItem>,
+) -> bool {
+    if actions.just_pressed(Action::Deselect) {
+        match page.get() {
+            PausePage::Menu => pause.set(PauseState::Running),
+            PausePage::Settings => page_state.set(PausePage::Menu),
+            PausePage::Controls => page_state.set(PausePage::Settings),
+        }
+        return false;
+    }
+
+    let count = items.iter().count();
+    if count == 0 {
+        return false;
+    }
+
+    if actions.nav_just_pressed(Nav::Down) || actions.nav_just_pressed(Nav::Next) {
+        focus.0 = Some(focus.0.map_or(0, |index| (index + 1) % count));
+    }
+
+    if actions.nav_just_pressed(Nav::Up) || actions.nav_just_pressed(Nav::Previous) {
+        focus.0 = Some(
+            focus
+                .0
+                .map_or(count - 1, |index| (index + count - 1) % count),
+        );
+    }
+
+    actions.nav_just_pressed(Nav::Select) || actions.just_pressed(Action::Confirm)
+}
+
+fn press_buttons(
+    In(confirmed): In<bool>,
+    mut pause: ResMut<NextState<PauseState>>,
+    mut page: ResMut<NextState<PausePage>>,
+    mut state: ResMut<NextState<AppState>>,
+    mut board_state: ResMut<NextState<BoardState>>,
+    mut exit: MessageWriter<AppExit>,
+    mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+    focus: Res<MenuFocus>,
+    current_page: Res<State<PausePage>>,
+    buttons: Query<(Ref<Interaction>, &PauseButton, &MenuItem, Has<Locked>)>,
+) {
+    // Holding the mouse down only presses a button once.
+    let pressed = buttons.iter().find(|(interaction, _, item, locked)| {
+        let clicked = interaction.is_changed() && **interaction == Interaction::Pressed;
+        !locked && (clicked || confirmed && focus.0 == Some(item.0))
+    });
+
+    let Some((_, button, _, _)) = pressed else {
+        return;
+    };
+
+    match button {
+        PauseButton::Resume => pause.set(PauseState::Running),
+        // Entering the board again sets it up from the starting layout, and each mode resets its
+        // own game on entry. Substates carry over a same-state transition, so they are reset here.
+        PauseButton::Restart => {
+            state.set(AppState::Main);
+            board_state.set(BoardState::Playing);
+            pause.set(PauseState::Running);
+        }
+        PauseButton::Settings => page.set(PausePage::Settings),
+        PauseButton::Title => state.set(AppState::Title),
+        PauseButton::Quit => {
+            exit.write(AppExit::Success);
+        }
+        PauseButton::Volume => {
+            let current = VOLUMES
+                .iter()
+                .position(|volume| *volume == settings.volume)
+                .unwrap_or(0);
+            settings.volume = VOLUMES[(current + 1) % VOLUMES.len()];
+        }
+        PauseButton::HealthBars => settings.health_bars = !settings.health_bars,
+        PauseButton::MoveSpeed => {
+            let current = MOVE_SPEEDS
+                .iter()
+                .position(|(secs, _)| *secs == settings.move_duration)
+                .unwrap_or(0);
+            settings.move_duration = MOVE_SPEEDS[(current + 1) % MOVE_SPEEDS.len()].0;
+        }
+        PauseButton::Theme => {
+            let current = Theme::ALL
+                .iter()
+                .position(|theme| *theme == settings.theme)
+                .unwrap_or(0);
+            settings.theme = Theme::ALL[(current + 1) % Theme::ALL.len()];
+        }
+        PauseButton::Controls => page.set(PausePage::Controls),
+        PauseButton::Bind(action) => rebinding.0 = Some(*action),
+        PauseButton::ResetBindings => settings.bindings = Bindings::default(),
+        PauseButton::Back => match current_page.get() {
+            PausePage::Controls => page.set(PausePage::Settings),
+            _ => page.set(PausePage::Menu),
+        },
+    }
+}
+
+fn button_label(button: PauseButton, settings: &Settings, rebinding: Option<Action>) -> String {
+    match button {
+        PauseButton::Resume => "Resume".to_string(),
+        PauseButton::Restart => "Restart".to_string(),
+        PauseButton::Settings => "Settings".to_string(),
+        PauseButton::Title => "Return to Title".to_string(),
+        PauseButton::Quit => "Quit".to_string(),
+        PauseButton::Volume => format!("Volume: {:.0}%", settings.volume * 100.0),
+        PauseButton::HealthBars => {
+            format!(
+                "Health Bars: {}",
+                if settings.health_bars { "On" } else { "Off" }
+            )
+        }
+        PauseButton::MoveSpeed => {
+            let name = MOVE_SPEEDS
+                .iter()
+                .find(|(secs, _)| *secs == settings.move_duration)
+                .map_or("Custom", |(_, name)| name);
+            format!("Move Speed: {name}")
+        }
+        PauseButton::Theme => format!("Theme: {}", settings.theme.name()),
+        PauseButton::Controls => "Controls".to_string(),
+        PauseButton::Bind(action) if rebinding == Some(action) => {
+            format!("{}: press a key...", action.name())
+        }
+        PauseButton::Bind(action) => {
+            format!("{}: {}", action.name(), settings.bindings.prompt(action))
+        }
+        PauseButton::ResetBindings => "Reset to Defaults".to_string(),
+        PauseButton::Back => "Back".to_string(),
+    }
+}
+
+fn update_button_labels(
+    mut buttons: Query<(&PauseButton, &mut Text)>,
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+) {
+    for (button, mut text) in &mut buttons {
+        let label = button_label(*button, &settings, rebinding.0);
+
+        if text.0 != label {
+            text.0 = label;
+        }
+    }
+}
+
+fn color_buttons(
+    mut buttons: Query<(&Interaction, &MenuItem, Has<Locked>, &mut BackgroundColor)>,
+    focus: Res<MenuFocus>,
+) {
+    for (interaction, item, locked, mut bg) in &mut buttons {
+        bg.0 = if locked {
+            BUTTON_LOCKED
+        } else if *interaction != Interaction::None || focus.0 == Some(item.0) {
+            BUTTON_HOVER
+        } else {
+            BUTTON
+        };
+    }
+}
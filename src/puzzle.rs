@@ -0,0 +1,272 @@
+//! Puzzle mode. Each [`Puzzle`] is an authored position with a goal that is checked after every
+//! pass. Puzzles are listed in a [`PuzzlePack`] and played in order.
+
+use crate::{
+    AppState, BoardState, GameMode,
+    actions::{Action, Actions},
+    ai::Opponent,
+    assets::{FontsCollection, PuzzlesCollection},
+    authored::{Outcome, Puzzle},
+    chessboard::StartingLayout,
+    goal::GoalProgress,
+    title::{TitleMode, mode_chosen},
+};
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::Deserialize;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Puzzle>();
+    app.init_asset::<PuzzlePack>();
+    app.init_asset_loader::<PuzzleLoader>();
+    app.init_asset_loader::<PuzzlePackLoader>();
+
+    app.add_systems(
+        Update,
+        start_puzzles.run_if(in_state(AppState::Title).and(mode_chosen(TitleMode::Puzzles))),
+    );
+    app.add_systems(OnEnter(AppState::Main), setup.run_if(in_puzzle_mode));
+    app.add_systems(
+        Update,
+        (
+            update_goal_text.run_if(resource_changed::<GoalProgress>),
+            puzzle_controls,
+        )
+            .chain()
+            .run_if(
+                in_state(AppState::Main)
+                    .and(in_puzzle_mode)
+                    .and(resource_exists::<GoalProgress>),
+            ),
+    );
+}
+
+#[derive(Asset, TypePath, Debug)]
+pub struct PuzzlePack {
+    #[dependency]
+    pub puzzles: Vec<Handle<Puzzle>>,
+}
+
+#[derive(Deserialize)]
+struct PuzzlePackFile {
+    puzzles: Vec<String>,
+}
+
+#[derive(Default, TypePath)]
+struct PuzzleLoader;
+
+impl AssetLoader for PuzzleLoader {
+    type Asset = Puzzle;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["puzzle.ron"]
+    }
+}
+
+#[derive(Default, TypePath)]
+struct PuzzlePackLoader;
+
+impl AssetLoader for PuzzlePackLoader {
+    type Asset = PuzzlePack;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: PuzzlePackFile = ron::de::from_bytes(&bytes)?;
+
+        Ok(PuzzlePack {
+            puzzles: file
+                .puzzles
+                .into_iter()
+                .map(|path| load_context.load(path))
+                .collect(),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pack.ron"]
+    }
+}
+
+#[derive(Resource)]
+struct PuzzleName(String);
+
+#[derive(Component)]
+struct PuzzleGoalText;
+
+fn in_puzzle_mode(mode: Res<GameMode>) -> bool {
+    matches!(*mode, GameMode::Puzzle(_))
+}
+
+fn get_puzzle<'a>(
+    index: usize,
+    collection: &PuzzlesCollection,
+    packs: &Assets<PuzzlePack>,
+    puzzles: &'a Assets<Puzzle>,
+) -> Option<&'a Puzzle> {
+    let pack = packs.get(&collection.pack)?;
+    puzzles.get(pack.puzzles.get(index)?)
+}
+
+fn start_puzzles(
+    mut state: ResMut<NextState<AppState>>,
+    mut mode: ResMut<GameMode>,
+    mut layout: ResMut<StartingLayout>,
+    mut opponent: ResMut<Opponent>,
+    collection: Res<PuzzlesCollection>,
+    packs: Res<Assets<PuzzlePack>>,
+    puzzles: Res<Assets<Puzzle>>,
+) {
+    let Some(puzzle) = get_puzzle(0, &collection, &packs, &puzzles) else {
+        warn!("puzzle pack is empty");
+        return;
+    };
+
+    *mode = GameMode::Puzzle(0);
+    *layout = StartingLayout(puzzle.board());
+    *opponent = puzzle.opponent;
+    state.set(AppState::Main);
+}
+
+fn setup(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    layout: Res<StartingLayout>,
+    font: Res<FontsCollection>,
+    collection: Res<PuzzlesCollection>,
+    packs: Res<Assets<PuzzlePack>>,
+    puzzles: Res<Assets<Puzzle>>,
+) {
+    let GameMode::Puzzle(index) = *mode else {
+        return;
+    };
+
+    let Some(puzzle) = get_puzzle(index, &collection, &packs, &puzzles) else {
+        warn!("puzzle {index} missing from pack");
+        return;
+    };
+
+    commands.insert_resource(GoalProgress::new(puzzle.goal, &layout.0));
+    commands.insert_resource(PuzzleName(puzzle.name.clone()));
+
+    commands.spawn((
+        Name::new("Puzzle Panel"),
+        DespawnOnExit(AppState::Main),
+        Node {
+            position_type: PositionType::Absolute,
+            left: px(10.0),
+            bottom: px(10.0),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Name::new("Puzzle Goal Text"),
+            Text::default(),
+            PuzzleGoalText,
+            TextFont {
+                font: font.title.clone(),
+                font_size: 28.0,
+                ..default()
+            },
+        )],
+    ));
+}
+
+fn update_goal_text(
+    mut text_query: Query<&mut Text, With<PuzzleGoalText>>,
+    progress: Res<GoalProgress>,
+    name: Res<PuzzleName>,
+    actions: Actions,
+) {
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+
+    let retry = actions.prompt(Action::Retry);
+    let status = match progress.outcome {
+        None => format!(
+            "Passes: {}/{}\nPress {retry} to retry",
+            progress.passes,
+            progress.goal.limit()
+        ),
+        Some(Outcome::Won) => format!(
+            "Solved!\nPress {} for the next puzzle",
+            actions.prompt(Action::Confirm)
+        ),
+        Some(Outcome::Lost) => format!("Failed!\nPress {retry} to retry"),
+    };
+
+    text.0 = format!(
+        "Puzzle: {}\n{}\n{}",
+        name.0,
+        progress.goal.description(),
+        status
+    );
+}
+
+fn puzzle_controls(
+    mut state: ResMut<NextState<AppState>>,
+    mut board_state: ResMut<NextState<BoardState>>,
+    mut mode: ResMut<GameMode>,
+    mut layout: ResMut<StartingLayout>,
+    mut opponent: ResMut<Opponent>,
+    actions: Actions,
+    progress: Res<GoalProgress>,
+    collection: Res<PuzzlesCollection>,
+    packs: Res<Assets<PuzzlePack>>,
+    puzzles: Res<Assets<Puzzle>>,
+) {
+    let GameMode::Puzzle(index) = *mode else {
+        return;
+    };
+
+    if actions.just_pressed(Action::Retry) {
+        state.set(AppState::Main);
+        board_state.set(BoardState::Playing);
+        return;
+    }
+
+    if !actions.just_pressed(Action::Confirm) || progress.outcome != Some(Outcome::Won) {
+        return;
+    }
+
+    match get_puzzle(index + 1, &collection, &packs, &puzzles) {
+        Some(next) => {
+            *mode = GameMode::Puzzle(index + 1);
+            *layout = StartingLayout(next.board());
+            *opponent = next.opponent;
+            state.set(AppState::Main);
+            board_state.set(BoardState::Playing);
+        }
+        None => {
+            info!("Finished all puzzles");
+            *mode = GameMode::Standard;
+            *layout = StartingLayout::default();
+            *opponent = Opponent::default();
+            state.set(AppState::Title);
+        }
+    }
+}
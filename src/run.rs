@@ -0,0 +1,483 @@
+//! Run mode. The white army fights battle after battle against ever larger black armies, keeping
+//! its surviving pieces and their health between fights. After each win the player picks one of a
+//! few [`Reward`]s, and the run ends when the white king dies.
+
+use crate::{
+    AppState, BUTTON, BUTTON_HOVER, BoardState, GameMode,
+    actions::{Action, Actions, Nav},
+    ai::Opponent,
+    assets::FontsCollection,
+    authored::Outcome,
+    behaviour::*,
+    chessboard::{StartingLayout, TurnPassed},
+    stats::ActionBudget,
+    title::{TitleMode, mode_chosen},
+};
+use bevy::prelude::*;
+use rand::prelude::*;
+
+/// Extra damage granted by a [`Reward::Sharpen`].
+const SHARPEN_DAMAGE: f32 = 5.0;
+/// Recruits stop being offered once the army is this large.
+const MAX_ARMY: usize = 16;
+/// Action points stop being offered once the budget is this large.
+const MAX_ACTIONS: u8 = 6;
+const REWARD_CHOICES: usize = 3;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(AppState::Title), end_run);
+    app.add_systems(
+        Update,
+        start_run.run_if(in_state(AppState::Title).and(mode_chosen(TitleMode::Run))),
+    );
+
+    app.add_systems(OnEnter(AppState::Main), setup.run_if(in_run_mode));
+    app.add_systems(
+        Update,
+        (check_battle, update_run_text, run_controls)
+            .chain()
+            .run_if(
+                in_state(AppState::Main)
+                    .and(in_run_mode)
+                    .and(resource_exists::<Run>),
+            ),
+    );
+
+    app.add_systems(OnEnter(AppState::Rewards), spawn_rewards);
+    app.add_systems(
+        Update,
+        (focus_reward, color_reward_buttons, click_reward_button)
+            .chain()
+            .run_if(in_state(AppState::Rewards)),
+    );
+}
+
+/// State carried from battle to battle.
+#[derive(Resource, Debug)]
+pub struct Run {
+    /// White pieces that survived the last battle, with their current health.
+    pub army: Vec<Piece>,
+    /// The battle being fought, counting from 1.
+    pub battle: u32,
+    pub outcome: Option<Outcome>,
+    rewards: Vec<Reward>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Reward {
+    /// Restore every piece to full health.
+    Heal,
+    /// Add a new piece to the army.
+    Recruit(PieceKind),
+    /// Raise the damage of the piece at this index in the army.
+    Sharpen(usize),
+    /// One more move per pass.
+    ActionPoint,
+}
+
+impl Reward {
+    fn description(self, army: &[Piece]) -> String {
+        match self {
+            Reward::Heal => "Heal every piece to full health".to_string(),
+            Reward::Recruit(kind) => format!("Recruit a {kind:?}"),
+            Reward::Sharpen(index) => {
+                let piece = army[index];
+                format!(
+                    "+{SHARPEN_DAMAGE} damage for your {:?} ({} HP)",
+                    piece.kind, piece.health
+                )
+            }
+            Reward::ActionPoint => "+1 move per pass".to_string(),
+        }
+    }
+
+    fn apply(self, army: &mut Vec<Piece>, budget: &mut ActionBudget) {
+        match self {
+            Reward::Heal => {
+                for piece in army {
+                    piece.health = piece.max_health();
+                }
+            }
+            Reward::Recruit(kind) => army.push(Piece::new(PieceColor::White, kind)),
+            Reward::Sharpen(index) => army[index].damage += SHARPEN_DAMAGE,
+            Reward::ActionPoint => budget.0 += 1,
+        }
+    }
+}
+
+#[derive(Component)]
+struct RunText;
+
+#[derive(Component)]
+struct RewardButton(usize);
+
+fn in_run_mode(mode: Res<GameMode>) -> bool {
+    *mode == GameMode::Run
+}
+
+/// Draws up to [`REWARD_CHOICES`] different kinds of reward.
+fn draw_rewards(army: &[Piece], budget: &ActionBudget, rng: &mut impl Rng) -> Vec<Reward> {
+    let mut pool = vec![
+        Reward::Heal,
+        Reward::Sharpen(rng.random_range(..army.len())),
+    ];
+
+    if army.len() < MAX_ARMY {
+        let kinds = [
+            PieceKind::Pawn,
+            PieceKind::Knight,
+            PieceKind::Bishop,
+            PieceKind::Rook,
+        ];
+        pool.push(Reward::Recruit(*kinds.choose(rng).unwrap()));
+    }
+
+    if budget.0 < MAX_ACTIONS {
+        pool.push(Reward::ActionPoint);
+    }
+
+    pool.shuffle(rng);
+    pool.truncate(REWARD_CHOICES);
+    pool
+}
+
+/// Places `piece` on the first free square of `preferred`, or else anywhere in white's three
+/// home ranks.
+fn place(board: &mut Board, piece: Piece, preferred: &[GridCoords]) {
+    let home = (5..8).flat_map(|y| (0..8).map(move |x| GridCoords::new(x, y)));
+
+    if let Some(square) = preferred
+        .iter()
+        .copied()
+        .chain(home)
+        .find(|square| board.get(*square).is_none())
+    {
+        board.set(square, Some(piece));
+    }
+}
+
+/// Lines the army up in its home ranks and fills the other side with a black army that grows
+/// with each battle.
+fn battle_layout(battle: u32, army: &[Piece], rng: &mut impl Rng) -> Board {
+    let mut board = Board::default();
+
+    let back_rank: Vec<GridCoords> = [4, 3, 5, 2, 6, 1, 7, 0]
+        .into_iter()
+        .map(|x| GridCoords::new(x, 7))
+        .collect();
+    let pawn_rank: Vec<GridCoords> = [3, 4, 2, 5, 1, 6, 0, 7]
+        .into_iter()
+        .map(|x| GridCoords::new(x, 6))
+        .collect();
+
+    let mut army = army.to_vec();
+    army.sort_by_key(|piece| piece.kind != PieceKind::King);
+
+    for piece in army {
+        let preferred = if piece.kind == PieceKind::Pawn {
+            &pawn_rank
+        } else {
+            &back_rank
+        };
+        place(&mut board, piece, preferred);
+    }
+
+    board.set(
+        GridCoords::new(4, 0),
+        Some(Piece::new(PieceColor::Black, PieceKind::King)),
+    );
+
+    let mut officer_squares = vec![0, 1, 2, 3, 5, 6, 7];
+    officer_squares.shuffle(rng);
+    let mut officer_kinds = vec![PieceKind::Knight, PieceKind::Bishop, PieceKind::Rook];
+    if battle >= 3 {
+        officer_kinds.push(PieceKind::Queen);
+    }
+
+    for x in officer_squares.into_iter().take(battle as usize + 1) {
+        let kind = *officer_kinds.choose(rng).unwrap();
+        board.set(
+            GridCoords::new(x, 0),
+            Some(Piece::new(PieceColor::Black, kind)),
+        );
+    }
+
+    let mut pawn_squares: Vec<i32> = (0..8).collect();
+    pawn_squares.shuffle(rng);
+
+    for x in pawn_squares.into_iter().take(battle as usize + 3) {
+        board.set(
+            GridCoords::new(x, 1),
+            Some(Piece::new(PieceColor::Black, PieceKind::Pawn)),
+        );
+    }
+
+    board
+}
+
+fn end_run(mut commands: Commands, mut budget: ResMut<ActionBudget>) {
+    commands.remove_resource::<Run>();
+    *budget = ActionBudget::default();
+}
+
+fn start_run(
+    mut commands: Commands,
+    mut state: ResMut<NextState<AppState>>,
+    mut mode: ResMut<GameMode>,
+    mut layout: ResMut<StartingLayout>,
+    mut opponent: ResMut<Opponent>,
+) {
+    let army: Vec<Piece> = StartingLayout::default()
+        .0
+        .pieces()
+        .map(|(_, piece)| piece)
+        .filter(|piece| piece.color == PieceColor::White)
+        .collect();
+
+    *layout = StartingLayout(battle_layout(1, &army, &mut rand::rng()));
+    *mode = GameMode::Run;
+    *opponent = Opponent::Random;
+    commands.insert_resource(Run {
+        army,
+        battle: 1,
+        outcome: None,
+        rewards: Vec::new(),
+    });
+    state.set(AppState::Main);
+}
+
+/// Fights the battle from its starting layout. Only a won battle moves the run on, so restarting
+/// one goes back to how it began.
+fn setup(mut commands: Commands, mut run: ResMut<Run>, font: Res<FontsCollection>) {
+    run.outcome = None;
+    run.rewards.clear();
+
+    commands.spawn((
+        Name::new("Run Panel"),
+        DespawnOnExit(AppState::Main),
+        Node {
+            position_type: PositionType::Absolute,
+            left: px(10.0),
+            bottom: px(10.0),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Name::new("Run Text"),
+            Text::default(),
+            RunText,
+            TextFont {
+                font: font.title.clone(),
+                font_size: 28.0,
+                ..default()
+            },
+        )],
+    ));
+}
+
+fn check_battle(
+    mut turn_passed: MessageReader<TurnPassed>,
+    mut run: ResMut<Run>,
+    mut board_state: ResMut<NextState<BoardState>>,
+    budget: Res<ActionBudget>,
+    chessgrid: Res<ChessGrid>,
+    pieces: Query<&Piece>,
+) {
+    for _ in turn_passed.read() {
+        if run.outcome.is_some() {
+            continue;
+        }
+
+        let remaining: Vec<Piece> = chessgrid
+            .pieces
+            .iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entity| pieces.get(*entity).ok().copied())
+            .collect();
+
+        let king_alive = |color: PieceColor| {
+            remaining
+                .iter()
+                .any(|piece| piece.color == color && piece.kind == PieceKind::King)
+        };
+
+        if !king_alive(PieceColor::White) {
+            info!("Run ended in battle {}", run.battle);
+            run.outcome = Some(Outcome::Lost);
+        } else if !king_alive(PieceColor::Black) {
+            let army: Vec<Piece> = remaining
+                .into_iter()
+                .filter(|piece| piece.color == PieceColor::White)
+                .collect();
+
+            run.rewards = draw_rewards(&army, &budget, &mut rand::rng());
+            run.army = army;
+            run.outcome = Some(Outcome::Won);
+        } else {
+            continue;
+        }
+
+        board_state.set(BoardState::Finished);
+    }
+}
+
+fn update_run_text(
+    mut text_query: Query<&mut Text, With<RunText>>,
+    run: Res<Run>,
+    actions: Actions,
+) {
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+
+    let confirm = actions.prompt(Action::Confirm);
+
+    text.0 = match run.outcome {
+        None => format!(
+            "Run: Battle {}\nKill the Black King\nand keep yours alive",
+            run.battle
+        ),
+        Some(Outcome::Won) => {
+            format!(
+                "Battle {} won!\nPress {confirm} to\nchoose a reward",
+                run.battle
+            )
+        }
+        Some(Outcome::Lost) => format!(
+            "The King has fallen!\nBattles won: {}\nPress {confirm} to return",
+            run.battle - 1
+        ),
+    };
+}
+
+fn run_controls(mut state: ResMut<NextState<AppState>>, actions: Actions, run: Res<Run>) {
+    if !actions.just_pressed(Action::Confirm) {
+        return;
+    }
+
+    match run.outcome {
+        Some(Outcome::Won) => state.set(AppState::Rewards),
+        Some(Outcome::Lost) => state.set(AppState::Title),
+        None => {}
+    }
+}
+
+/// Index of the reward picked with the arrow keys or a pad.
+#[derive(Resource, Default)]
+struct RewardFocus(usize);
+
+fn spawn_rewards(mut commands: Commands, font: Res<FontsCollection>, run: Res<Run>) {
+    commands.insert_resource(RewardFocus::default());
+    commands
+        .spawn((
+            Name::new("Rewards Root"),
+            DespawnOnExit(AppState::Rewards),
+            Node {
+                width: percent(100.0),
+                height: percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: px(10.0),
+                ..default()
+            },
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Name::new("Rewards Title"),
+                Text::new("Choose a Reward"),
+                TextFont {
+                    font: font.title.clone(),
+                    font_size: 70.0,
+                    ..default()
+                },
+            ));
+
+            for (index, reward) in run.rewards.iter().enumerate() {
+                p.spawn((
+                    Name::new("Reward Button"),
+                    Button,
+                    RewardButton(index),
+                    Node {
+                        width: px(500.0),
+                        padding: px(10.0).into(),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    BackgroundColor(BUTTON),
+                    children![(
+                        Text::new(reward.description(&run.army)),
+                        TextFont {
+                            font: font.title.clone(),
+                            font_size: 28.0,
+                            ..default()
+                        },
+                        Pickable::IGNORE,
+                    )],
+                ));
+            }
+        });
+}
+
+fn focus_reward(mut focus: ResMut<RewardFocus>, actions: Actions, run: Res<Run>) {
+    let count = run.rewards.len();
+    if count == 0 {
+        return;
+    }
+
+    if actions.nav_just_pressed(Nav::Down) || actions.nav_just_pressed(Nav::Next) {
+        focus.0 = (focus.0 + 1) % count;
+    }
+
+    if actions.nav_just_pressed(Nav::Up) || actions.nav_just_pressed(Nav::Previous) {
+        focus.0 = (focus.0 + count - 1) % count;
+    }
+}
+
+fn color_reward_buttons(
+    mut query: Query<(&Interaction, &RewardButton, &mut BackgroundColor)>,
+    focus: Res<RewardFocus>,
+) {
+    for (interaction, button, mut bg) in &mut query {
+        bg.0 = if *interaction == Interaction::None && focus.0 != button.0 {
+            BUTTON
+        } else {
+            BUTTON_HOVER
+        };
+    }
+}
+
+fn click_reward_button(
+    mut state: ResMut<NextState<AppState>>,
+    mut run: ResMut<Run>,
+    mut budget: ResMut<ActionBudget>,
+    mut layout: ResMut<StartingLayout>,
+    query: Query<(Ref<Interaction>, &RewardButton)>,
+    focus: Res<RewardFocus>,
+    actions: Actions,
+) {
+    let confirmed = actions.just_pressed(Action::Confirm) || actions.nav_just_pressed(Nav::Select);
+
+    for (interaction, button) in &query {
+        // Holding the mouse down only picks the reward once.
+        let clicked = interaction.is_changed() && *interaction == Interaction::Pressed;
+        if !(clicked || confirmed && focus.0 == button.0) {
+            continue;
+        }
+
+        let Some(reward) = run.rewards.get(button.0).copied() else {
+            continue;
+        };
+
+        reward.apply(&mut run.army, &mut budget);
+
+        run.battle += 1;
+        run.outcome = None;
+        run.rewards.clear();
+        *layout = StartingLayout(battle_layout(run.battle, &run.army, &mut rand::rng()));
+        state.set(AppState::Main);
+        return;
+    }
+}
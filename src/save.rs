@@ -0,0 +1,115 @@
+//! Small RON files kept in the platform data or config directory, such as campaign progress and
+//! settings. Names may include subdirectories. Nothing is saved on the web.
+
+use serde::{Serialize, de::DeserializeOwned};
+use std::fmt::Display;
+#[cfg(not(target_arch = "wasm32"))]
+use {bevy::prelude::*, ron::ser::PrettyConfig, std::fs, std::io, std::path::PathBuf};
+
+/// The platform directory a file is kept in.
+#[derive(Clone, Copy, Debug)]
+pub enum Dir {
+    /// Progress and games in play. The functions without a [`Dir`] use this one.
+    Data,
+    /// Player preferences.
+    Config,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_dir(dir: Dir) -> Option<PathBuf> {
+    let base = match dir {
+        Dir::Data => dirs::data_dir(),
+        Dir::Config => dirs::config_dir(),
+    };
+
+    Some(base?.join("fever_dream_chess"))
+}
+
+/// Where `name` is kept, for pointing players at files they exchange by hand.
+pub fn path(name: &str) -> Option<std::path::PathBuf> {
+    path_in(Dir::Data, name)
+}
+
+pub fn load<T: DeserializeOwned>(name: &str) -> Option<T> {
+    load_with(Dir::Data, name, |text| ron::from_str(text))
+}
+
+pub fn store<T: Serialize>(name: &str, value: &T) {
+    store_in(Dir::Data, name, value);
+}
+
+pub fn remove(name: &str) {
+    remove_in(Dir::Data, name);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn path_in(dir: Dir, name: &str) -> Option<PathBuf> {
+    Some(save_dir(dir)?.join(name))
+}
+
+/// Reads `name` with `parse`, for files that need more than deserializing, such as upgrading
+/// older versions. A missing file is `None`, and so is one `parse` refuses, with a warning.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_with<T, E: Display>(
+    dir: Dir,
+    name: &str,
+    parse: impl FnOnce(&str) -> Result<T, E>,
+) -> Option<T> {
+    let path = path_in(dir, name)?;
+    let text = fs::read_to_string(&path).ok()?;
+
+    parse(&text)
+        .inspect_err(|err| warn!("ignoring unreadable save {}: {err}", path.display()))
+        .ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn store_in<T: Serialize>(dir: Dir, name: &str, value: &T) {
+    let Some(path) = path_in(dir, name) else {
+        return;
+    };
+    let parent = path.parent().unwrap_or(&path);
+
+    let result = fs::create_dir_all(parent).and_then(|()| {
+        let text =
+            ron::ser::to_string_pretty(value, PrettyConfig::default()).map_err(io::Error::other)?;
+        fs::write(&path, text)
+    });
+
+    if let Err(err) = result {
+        warn!("failed to save {}: {err}", path.display());
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remove_in(dir: Dir, name: &str) {
+    let Some(path) = path_in(dir, name) else {
+        return;
+    };
+
+    if let Err(err) = fs::remove_file(&path)
+        && err.kind() != io::ErrorKind::NotFound
+    {
+        warn!("failed to remove {}: {err}", path.display());
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn path_in(_dir: Dir, _name: &str) -> Option<std::path::PathBuf> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load_with<T, E: Display>(
+    _dir: Dir,
+    _name: &str,
+    _parse: impl FnOnce(&str) -> Result<T, E>,
+) -> Option<T> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn store_in<T: Serialize>(_dir: Dir, _name: &str, _value: &T) {}
+
+#[cfg(target_arch = "wasm32")]
+pub fn remove_in(_dir: Dir, _name: &str) {}
@@ -0,0 +1,212 @@
+//! Player preferences, kept as RON in the platform config directory and saved whenever they
+//! change. Every file records the [`SETTINGS_VERSION`] it was written with, and files from older
+//! versions are read in the layout they were written in, then converted. A file that cannot be
+//! read falls back to the defaults with a warning. Nothing is saved on the web.
+
+use crate::{
+    actions::{Action, Bindings},
+    save::{self, Dir},
+    theme::Theme,
+};
+use bevy::prelude::*;
+use bevy_seedling::prelude::{MainBus, Volume, VolumeNode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Bumped whenever [`Settings`] changes in a way its field defaults can't cover, together with a
+/// struct for the old layout that [`parse`] converts from. New fields alone need neither, as
+/// missing fields are defaulted.
+pub const SETTINGS_VERSION: u32 = 2;
+
+const FILE: &str = "settings.ron";
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(save::load_with(Dir::Config, FILE, parse).unwrap_or_default());
+    app.add_systems(
+        Update,
+        save_settings.run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+    );
+    app.add_systems(Update, apply_volume);
+}
+
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    /// Master volume, from 0 to 1.
+    pub volume: f32,
+    /// Seconds a piece takes to slide between squares. Zero moves pieces instantly.
+    pub move_duration: f32,
+    /// Whether health bars are drawn under the pieces.
+    pub health_bars: bool,
+    pub bindings: Bindings,
+    pub theme: Theme,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            volume: 1.0,
+            move_duration: 0.3,
+            health_bars: true,
+            bindings: Bindings::default(),
+            theme: Theme::default(),
+        }
+    }
+}
+
+/// The start of a settings file, read first to know how to read the rest.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+/// Version 1, which keyed bindings by the action itself. A file binding an action the reading
+/// version didn't know couldn't be read at all, so version 2 keys them by name.
+#[derive(Deserialize)]
+#[serde(default)]
+struct SettingsV1 {
+    volume: f32,
+    move_duration: f32,
+    health_bars: bool,
+    bindings: BindingsV1,
+    theme: Theme,
+}
+
+#[derive(Deserialize, Default)]
+struct BindingsV1(BTreeMap<Action, String>);
+
+impl Default for SettingsV1 {
+    fn default() -> Self {
+        let settings = Settings::default();
+
+        Self {
+            volume: settings.volume,
+            move_duration: settings.move_duration,
+            health_bars: settings.health_bars,
+            bindings: BindingsV1::default(),
+            theme: settings.theme,
+        }
+    }
+}
+
+impl From<SettingsV1> for Settings {
+    fn from(old: SettingsV1) -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            volume: old.volume,
+            move_duration: old.move_duration,
+            health_bars: old.health_bars,
+            bindings: old.bindings.0.into_iter().collect(),
+            theme: old.theme,
+        }
+    }
+}
+
+/// Reads settings written by this or an older version.
+fn parse(text: &str) -> Result<Settings, String> {
+    let Header { version } =
+        ron::from_str(text).map_err(|err| format!("bad schema version: {err}"))?;
+
+    match version {
+        1 => ron::from_str::<SettingsV1>(text)
+            .map(Settings::from)
+            .map_err(|err| err.to_string()),
+        SETTINGS_VERSION => ron::from_str(text).map_err(|err| err.to_string()),
+        _ => Err(format!(
+            "schema version {version} is not between 1 and {SETTINGS_VERSION}"
+        )),
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    save::store_in(Dir::Config, FILE, &*settings);
+}
+
+fn apply_volume(settings: Res<Settings>, mut master: Query<&mut VolumeNode, With<MainBus>>) {
+    for mut node in &mut master {
+        if settings.is_changed() || node.is_added() {
+            node.volume = Volume::Linear(settings.volume);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn current_files_round_trip() {
+        let mut settings = Settings {
+            volume: 0.25,
+            health_bars: false,
+            theme: Theme::Protanopia,
+            ..default()
+        };
+        settings.bindings.bind(Action::Pass, KeyCode::KeyO);
+
+        let text = ron::to_string(&settings).unwrap();
+
+        assert_eq!(parse(&text), Ok(settings));
+    }
+
+    #[test]
+    fn version_1_bindings_are_kept() {
+        let text = r#"(
+            version: 1,
+            volume: 0.5,
+            health_bars: false,
+            bindings: ({Pass: "KeyO", ToggleThreats: "KeyY"}),
+            theme: Deuteranopia,
+        )"#;
+
+        let settings = parse(text).unwrap();
+
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.volume, 0.5);
+        assert!(!settings.health_bars);
+        assert_eq!(settings.bindings.key(Action::Pass), KeyCode::KeyO);
+        assert_eq!(settings.bindings.key(Action::ToggleThreats), KeyCode::KeyY);
+        assert_eq!(settings.bindings.key(Action::Confirm), KeyCode::Space);
+        assert_eq!(settings.theme, Theme::Deuteranopia);
+    }
+
+    #[test]
+    fn unknown_versions_are_refused() {
+        assert!(parse("(version: 0)").is_err());
+        assert!(parse(&format!("(version: {})", SETTINGS_VERSION + 1)).is_err());
+    }
+
+    #[test]
+    fn corrupt_files_fall_back_to_the_defaults() {
+        for text in ["", "not settings", "(version: 2, volume: \"loud\")"] {
+            assert!(parse(text).is_err(), "{text:?}");
+            assert_eq!(parse(text).unwrap_or_default(), Settings::default());
+        }
+    }
+
+    #[test]
+    fn missing_fields_are_defaulted() {
+        let settings = parse(&format!("(version: {SETTINGS_VERSION}, volume: 0.75)")).unwrap();
+
+        assert_eq!(
+            settings,
+            Settings {
+                volume: 0.75,
+                ..default()
+            }
+        );
+    }
+
+    #[test]
+    fn bindings_of_unknown_actions_are_ignored() {
+        let text = format!(
+            r#"(version: {SETTINGS_VERSION}, bindings: ({{"Pass": "KeyO", "Jump": "KeyJ"}}))"#
+        );
+
+        let settings = parse(&text).unwrap();
+
+        assert_eq!(settings.bindings.key(Action::Pass), KeyCode::KeyO);
+    }
+}
@@ -0,0 +1,353 @@
+//! Exhaustive solver for puzzles, used to check authored puzzles for cooked solutions.
+//!
+//! Unless the puzzle's opponent is passive, black's moves are not known in advance, so the solver
+//! treats black as an adversary: a line only counts as winning if the goal is met against every
+//! possible black reply. Black may make anything from none to all of its moves in a turn, which
+//! covers every opponent, including players and external bots. The search is exponential in the
+//! number of passes and is only meant for small, hand-made positions.
+
+use crate::{
+    ai::Opponent,
+    authored::{Goal, Outcome, Puzzle},
+    behaviour::*,
+    stats::TURNS_PER_PASS,
+};
+use bevy::platform::collections::{HashMap, HashSet};
+
+/// The moves of every turn in a game, white's and black's in turn, starting with white's.
+pub type Line = Vec<Vec<Move>>;
+
+/// Every line in which white forces a win, played out until the goal is met. White's turns are
+/// only kept where they keep the win forced, while black's cover every reply. Move orders that
+/// reach the same position are listed once, with the fewest moves.
+pub fn solve(puzzle: &Puzzle) -> Vec<Line> {
+    let board = puzzle.board();
+    let mut solver = Solver {
+        goal: puzzle.goal,
+        opponent: puzzle.opponent,
+        starting_white: board
+            .pieces()
+            .filter(|(_, piece)| piece.color == PieceColor::White)
+            .count(),
+        memo: HashMap::default(),
+    };
+
+    let mut lines = solver.lines(&board, 0);
+    lines.sort_by_key(|line| format_line(line));
+    lines
+}
+
+/// Number of different first turns among the given lines. A well-made puzzle has exactly one.
+pub fn first_turns(lines: &[Line]) -> usize {
+    lines
+        .iter()
+        .filter_map(|line| line.first())
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// The turns of `line` separated by `|`, each as moves like `e2-e3`.
+pub fn format_line(line: &[Vec<Move>]) -> String {
+    line.iter()
+        .map(|turn| format_turn(turn))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn format_turn(turn: &[Move]) -> String {
+    if turn.is_empty() {
+        return "pass".to_string();
+    }
+
+    turn.iter()
+        .map(|(from, to)| format!("{from}-{to}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+struct Solver {
+    goal: Goal,
+    opponent: Opponent,
+    starting_white: usize,
+    memo: HashMap<(Board, u8), bool>,
+}
+
+impl Solver {
+    /// The winning lines from `board` with white to move, `passes` passes into the puzzle.
+    fn lines(&mut self, board: &Board, passes: u8) -> Vec<Line> {
+        let mut lines = Vec::new();
+
+        for (after_white, white_moves) in white_turns(board) {
+            if !self.wins_after_pass(&after_white, passes + 1) {
+                continue;
+            }
+
+            let mut damaged = after_white;
+            damaged.apply_damage_with_xp(PieceColor::White);
+
+            if let Goal::KillKing { .. } = self.goal
+                && self.outcome(&damaged, passes + 1) == Some(Outcome::Won)
+            {
+                lines.push(vec![white_moves]);
+                continue;
+            }
+
+            for (mut reply, black_moves) in black_replies(&damaged, self.opponent) {
+                reply.apply_damage_with_xp(PieceColor::Black);
+
+                if self.outcome(&reply, passes + 1).is_some() {
+                    lines.push(vec![white_moves.clone(), black_moves]);
+                    continue;
+                }
+
+                for rest in self.lines(&reply, passes + 1) {
+                    let mut line = vec![white_moves.clone(), black_moves.clone()];
+                    line.extend(rest);
+                    lines.push(line);
+                }
+            }
+        }
+
+        lines
+    }
+
+    /// Whether white forces a win when passing on `board`, `passes` being the pass count
+    /// after this one.
+    fn wins_after_pass(&mut self, board: &Board, passes: u8) -> bool {
+        if let Some(wins) = self.memo.get(&(*board, passes)) {
+            return *wins;
+        }
+
+        let mut after_white = *board;
+        after_white.apply_damage_with_xp(PieceColor::White);
+
+        // Black can't bring its king back or land a hit before its own attack phase, so a kill
+        // goal is already decided here. That's cheap to work out again, so it isn't remembered.
+        if let Goal::KillKing { .. } = self.goal
+            && let Some(outcome) = self.outcome(&after_white, passes)
+        {
+            return outcome == Outcome::Won;
+        }
+
+        let mut wins = true;
+
+        for mut reply in black_replies(&after_white, self.opponent).into_keys() {
+            reply.apply_damage_with_xp(PieceColor::Black);
+
+            let holds = match self.outcome(&reply, passes) {
+                Some(outcome) => outcome == Outcome::Won,
+                None => white_turns(&reply)
+                    .keys()
+                    .any(|next| self.wins_after_pass(next, passes + 1)),
+            };
+
+            if !holds {
+                wins = false;
+                break;
+            }
+        }
+
+        self.remember(board, passes, wins)
+    }
+
+    fn outcome(&self, board: &Board, passes: u8) -> Option<Outcome> {
+        self.goal.outcome(
+            passes,
+            self.starting_white,
+            board.pieces().map(|(_, piece)| piece),
+        )
+    }
+
+    fn remember(&mut self, board: &Board, passes: u8, wins: bool) -> bool {
+        self.memo.insert((*board, passes), wins);
+        wins
+    }
+}
+
+/// Every position white can reach with up to its full turn of moves, including passing straight
+/// away, each with the shortest sequence of moves leading to it.
+fn white_turns(board: &Board) -> HashMap<Board, Vec<Move>> {
+    let mut turns = HashMap::default();
+    white_lines(board, TURNS_PER_PASS, &mut Vec::new(), &mut turns);
+    turns
+}
+
+fn white_lines(
+    board: &Board,
+    actions: u8,
+    line: &mut Vec<Move>,
+    turns: &mut HashMap<Board, Vec<Move>>,
+) {
+    let shortest = turns.entry(*board).or_insert_with(|| line.clone());
+    if line.len() < shortest.len() {
+        *shortest = line.clone();
+    }
+
+    if actions == 0 {
+        return;
+    }
+
+    for (from, to) in board.moves_for(PieceColor::White) {
+        let mut next = *board;
+        next.move_piece(from, to);
+
+        line.push((from, to));
+        white_lines(&next, actions - 1, line, turns);
+        line.pop();
+    }
+}
+
+/// Every position black can reach in its turn, each with the shortest sequence of moves leading
+/// to it. A passive opponent never moves.
+fn black_replies(board: &Board, opponent: Opponent) -> HashMap<Board, Vec<Move>> {
+    let mut replies = HashMap::default();
+    replies.insert(*board, Vec::new());
+
+    if opponent == Opponent::Passive {
+        return replies;
+    }
+
+    let mut frontier = vec![*board];
+
+    for _ in 0..TURNS_PER_PASS {
+        let mut next_frontier = Vec::new();
+
+        for position in &frontier {
+            for (from, to) in position.moves_for(PieceColor::Black) {
+                let mut next = *position;
+                next.move_piece(from, to);
+
+                if !replies.contains_key(&next) {
+                    let mut moves = replies[position].clone();
+                    moves.push((from, to));
+                    replies.insert(next, moves);
+                    next_frontier.push(next);
+                }
+            }
+        }
+
+        frontier = next_frontier;
+    }
+
+    replies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::PathBuf};
+
+    fn puzzles_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets/puzzles")
+    }
+
+    fn load(name: &str) -> Puzzle {
+        Puzzle::read(puzzles_dir().join(format!("{name}.puzzle.ron"))).unwrap()
+    }
+
+    fn solutions(name: &str) -> Vec<String> {
+        solve(&load(name))
+            .iter()
+            .map(|line| format_line(line))
+            .collect()
+    }
+
+    /// Plays `line` out with both attack phases and returns the outcome it ends on.
+    fn replay(puzzle: &Puzzle, line: &Line) -> Option<Outcome> {
+        let mut board = puzzle.board();
+        let starting_white = board
+            .pieces()
+            .filter(|(_, piece)| piece.color == PieceColor::White)
+            .count();
+        let mut outcome = None;
+
+        for (index, turn) in line.iter().enumerate() {
+            let color = if index.is_multiple_of(2) {
+                PieceColor::White
+            } else {
+                PieceColor::Black
+            };
+
+            for (from, to) in turn {
+                assert_eq!(board.get(*from).map(|piece| piece.color), Some(color));
+                assert!(legal_moves(board.get(*from).unwrap(), *from, board).contains(to));
+                board.move_piece(*from, *to);
+            }
+
+            board.apply_damage_with_xp(color);
+            outcome = puzzle.goal.outcome(
+                (index / 2 + 1) as u8,
+                starting_white,
+                board.pieces().map(|(_, piece)| piece),
+            );
+        }
+
+        outcome
+    }
+
+    #[test]
+    fn black_may_stop_its_turn_early() {
+        let mut board = Board::default();
+        let king = GridCoords::new(4, 4);
+        board.set(king, Some(Piece::new(PieceColor::Black, PieceKind::King)));
+
+        let replies = black_replies(&board, Opponent::Random);
+
+        assert_eq!(replies[&board], Vec::new());
+        assert!(replies.values().any(|moves| moves.len() == 1));
+        assert!(
+            replies
+                .values()
+                .any(|moves| moves.len() == TURNS_PER_PASS as usize)
+        );
+        assert_eq!(black_replies(&board, Opponent::Passive).len(), 1);
+    }
+
+    #[test]
+    fn royal_hunt_needs_both_knights_on_the_king() {
+        assert_eq!(solutions("royal_hunt"), ["d5-e7 e7-g6 e5-f7"]);
+    }
+
+    #[test]
+    fn pincer_holds_both_squares_for_two_passes() {
+        assert_eq!(solutions("pincer"), ["c4-b6 e6-c7 | pass | pass"]);
+    }
+
+    #[test]
+    fn hold_the_line_starts_by_shielding_the_pawns_with_the_king() {
+        let lines = solutions("hold_the_line");
+
+        // Once the king covers the pawns, white can simply wait out the rooks.
+        assert!(lines.contains(&"b1-a2 a2-b3 | pass | pass | pass | pass | pass".to_string()));
+        assert!(
+            lines
+                .iter()
+                .all(|line| line.starts_with("b1-a2 a2-b3 | pass | "))
+        );
+    }
+
+    #[test]
+    fn every_puzzle_has_a_unique_solution() {
+        for entry in fs::read_dir(puzzles_dir()).unwrap() {
+            let path = entry.unwrap().path();
+            if !path.to_string_lossy().ends_with(".puzzle.ron") {
+                continue;
+            }
+
+            let puzzle = Puzzle::read(&path).unwrap();
+            let lines = solve(&puzzle);
+
+            assert_eq!(first_turns(&lines), 1, "{}", path.display());
+
+            for line in &lines {
+                assert_eq!(
+                    replay(&puzzle, line),
+                    Some(Outcome::Won),
+                    "{}: {}",
+                    path.display(),
+                    format_line(line)
+                );
+            }
+        }
+    }
+}
@@ -2,3 +2,16 @@ use bevy::prelude::*;
 
 #[derive(Resource)]
 pub struct TurnsStat(pub u8);
+
+/// Moves each side gets before its attack phase.
+pub const TURNS_PER_PASS: u8 = 3;
+
+/// Moves white gets before its attack phase. Runs can raise it above [`TURNS_PER_PASS`].
+#[derive(Resource)]
+pub struct ActionBudget(pub u8);
+
+impl Default for ActionBudget {
+    fn default() -> Self {
+        Self(TURNS_PER_PASS)
+    }
+}
@@ -0,0 +1,127 @@
+//! Board and piece color themes. The board reads every color it draws from the [`Palette`] of the
+//! theme picked in the settings, and recolors itself in place when the theme changes.
+
+use crate::behaviour::PieceColor;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Classic,
+    /// Blue, yellow and orange from the Okabe-Ito palette, for red-green colorblindness.
+    Deuteranopia,
+    /// Like [`Theme::Deuteranopia`] with no reds; heatmaps darken and lighten squares instead.
+    Protanopia,
+    /// Black and grey squares with saturated highlights and pieces.
+    HighContrast,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 4] = [
+        Theme::Classic,
+        Theme::Deuteranopia,
+        Theme::Protanopia,
+        Theme::HighContrast,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Classic => "Classic",
+            Theme::Deuteranopia => "Deuteranopia",
+            Theme::Protanopia => "Protanopia",
+            Theme::HighContrast => "High Contrast",
+        }
+    }
+
+    pub fn palette(self) -> &'static Palette {
+        match self {
+            Theme::Classic => &CLASSIC,
+            Theme::Deuteranopia => &DEUTERANOPIA,
+            Theme::Protanopia => &PROTANOPIA,
+            Theme::HighContrast => &HIGH_CONTRAST,
+        }
+    }
+}
+
+pub struct Palette {
+    pub dark: Color,
+    pub light: Color,
+    pub hover: Color,
+    pub legal: Color,
+    pub select: Color,
+    pub attack: Color,
+    /// Blended over the square under the keyboard cursor, so its highlight still shows through.
+    pub cursor: Color,
+    /// Heatmap tints for the damage the other side and the side to move would deal.
+    pub threat: Color,
+    pub coverage: Color,
+    /// Tints of each side's piece backgrounds.
+    pub white: Color,
+    pub black: Color,
+}
+
+impl Palette {
+    pub fn piece(&self, color: PieceColor) -> Color {
+        match color {
+            PieceColor::White => self.white,
+            PieceColor::Black => self.black,
+        }
+    }
+}
+
+const CLASSIC: Palette = Palette {
+    dark: Color::hsl(200.0, 1.0, 0.25),
+    light: Color::hsl(200.0, 1.0, 0.5),
+    hover: Color::hsl(200.0, 1.0, 0.8),
+    legal: Color::hsl(100.0, 0.5, 0.8),
+    select: Color::hsl(10.0, 0.5, 0.8),
+    attack: Color::hsl(50.0, 0.9, 0.5),
+    cursor: Color::hsl(300.0, 1.0, 0.7),
+    threat: Color::hsl(0.0, 1.0, 0.5),
+    coverage: Color::hsl(140.0, 1.0, 0.5),
+    white: Color::hsl(175.0, 1.0, 0.75),
+    black: Color::hsl(10.0, 1.0, 0.25),
+};
+
+const DEUTERANOPIA: Palette = Palette {
+    dark: Color::hsl(0.0, 0.0, 0.3),
+    light: Color::hsl(0.0, 0.0, 0.55),
+    hover: Color::hsl(0.0, 0.0, 0.8),
+    legal: Color::srgb_u8(0x56, 0xb4, 0xe9),
+    select: Color::srgb_u8(0xf0, 0xe4, 0x42),
+    attack: Color::srgb_u8(0xd5, 0x5e, 0x00),
+    cursor: Color::srgb_u8(0xcc, 0x79, 0xa7),
+    threat: Color::srgb_u8(0xe6, 0x9f, 0x00),
+    coverage: Color::srgb_u8(0x00, 0x72, 0xb2),
+    white: Color::hsl(50.0, 1.0, 0.8),
+    black: Color::hsl(205.0, 1.0, 0.3),
+};
+
+const PROTANOPIA: Palette = Palette {
+    dark: Color::hsl(220.0, 0.25, 0.3),
+    light: Color::hsl(220.0, 0.25, 0.55),
+    hover: Color::hsl(220.0, 0.25, 0.8),
+    legal: Color::srgb_u8(0x56, 0xb4, 0xe9),
+    select: Color::srgb_u8(0xf0, 0xe4, 0x42),
+    attack: Color::srgb_u8(0xe6, 0x9f, 0x00),
+    cursor: Color::WHITE,
+    threat: Color::BLACK,
+    coverage: Color::WHITE,
+    white: Color::hsl(200.0, 1.0, 0.8),
+    black: Color::hsl(40.0, 1.0, 0.35),
+};
+
+const HIGH_CONTRAST: Palette = Palette {
+    dark: Color::BLACK,
+    light: Color::hsl(0.0, 0.0, 0.35),
+    hover: Color::hsl(0.0, 0.0, 0.6),
+    legal: Color::hsl(120.0, 1.0, 0.5),
+    select: Color::hsl(190.0, 1.0, 0.5),
+    attack: Color::hsl(0.0, 1.0, 0.5),
+    cursor: Color::hsl(300.0, 1.0, 0.6),
+    threat: Color::hsl(0.0, 1.0, 0.5),
+    coverage: Color::hsl(190.0, 1.0, 0.5),
+    white: Color::hsl(60.0, 1.0, 0.5),
+    black: Color::hsl(300.0, 1.0, 0.45),
+};
@@ -1,34 +1,113 @@
-use crate::{AppState, Typewriter, assets::FontsCollection};
+use crate::{
+    AppState, GameMode, Typewriter,
+    actions::{Action, Actions, Nav, PromptText},
+    ai::Opponent,
+    assets::FontsCollection,
+    chessboard::StartingLayout,
+};
 use bevy::prelude::*;
 
 const TITLE: &str = "Fever Dream Chess";
-const PRESS_TO_PLAY: &str = "Press [SPACE] to PLay";
+/// Seconds between the letters of the title screen texts.
+const TYPE_DELAY: f32 = 0.05;
+const FOCUSED: Color = Color::WHITE;
+const UNFOCUSED: Color = Color::hsl(0.0, 0.0, 0.5);
+
+/// The modes on the title screen, from the top, with their labels.
+const MODES: &[(TitleMode, &str)] = &[
+    (TitleMode::Standard, "Play"),
+    (TitleMode::Puzzles, "Puzzles"),
+    (TitleMode::Campaign, "Campaign"),
+    (TitleMode::Run, "Start a Run"),
+    (TitleMode::Waves, "Waves"),
+    (TitleMode::Hotseat, "Hotseat"),
+    #[cfg(not(target_arch = "wasm32"))]
+    (TitleMode::Lan, "LAN"),
+    (TitleMode::Mail, "Play by Mail"),
+];
 
 pub(super) fn plugin(app: &mut App) {
+    app.add_message::<ModeChosen>();
+    app.init_resource::<TitleFocus>();
     app.add_systems(OnEnter(AppState::Title), setup);
-    app.add_systems(Update, press_space.run_if(in_state(AppState::Title)));
+    app.add_systems(
+        Update,
+        (choose_mode, color_modes)
+            .chain()
+            .run_if(in_state(AppState::Title)),
+    );
+    app.add_systems(
+        Update,
+        start_standard.run_if(in_state(AppState::Title).and(mode_chosen(TitleMode::Standard))),
+    );
+}
+
+/// An entry of the title screen. Each mode's plugin starts it once it is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleMode {
+    Standard,
+    Puzzles,
+    Campaign,
+    Run,
+    Waves,
+    Hotseat,
+    /// Sockets and threads aren't available on the web.
+    #[cfg(not(target_arch = "wasm32"))]
+    Lan,
+    Mail,
 }
 
+#[derive(Message, Debug, Clone, Copy)]
+pub struct ModeChosen(pub TitleMode);
+
+/// Index into [`MODES`] of the focused entry, kept when coming back to the title.
+#[derive(Resource, Default)]
+struct TitleFocus(usize);
+
 #[derive(Component)]
 struct TitleText;
 
 #[derive(Component)]
-struct PressToPlayText;
-
-fn setup(mut commands: Commands, fonts: Res<FontsCollection>) {
-    commands.spawn((
-        Name::new("Main Node"),
-        DespawnOnExit(AppState::Title),
-        Node {
-            width: percent(100.0),
-            height: percent(100.0),
-            justify_content: JustifyContent::Center,
-            align_items: AlignItems::Center,
-            flex_direction: FlexDirection::Column,
-            ..default()
-        },
-        children![
-            (
+struct ModeLabel(usize);
+
+/// Run condition for the system starting `mode` from the title screen.
+pub fn mode_chosen(mode: TitleMode) -> impl FnMut(MessageReader<ModeChosen>) -> bool + Clone {
+    move |mut chosen: MessageReader<ModeChosen>| {
+        let mut picked = false;
+        for ModeChosen(choice) in chosen.read() {
+            picked |= *choice == mode;
+        }
+        picked
+    }
+}
+
+fn setup(mut commands: Commands, fonts: Res<FontsCollection>, focus: Res<TitleFocus>) {
+    let typewriter = |text: &str| Typewriter {
+        full_text: text.to_string(),
+        visible_chars: 0,
+        timer: Timer::from_seconds(TYPE_DELAY, TimerMode::Repeating),
+    };
+    let font = TextFont {
+        font: fonts.title.clone(),
+        font_size: 20.0,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            Name::new("Main Node"),
+            DespawnOnExit(AppState::Title),
+            Node {
+                width: percent(100.0),
+                height: percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+        ))
+        .with_children(|p| {
+            p.spawn((
                 Name::new("Title"),
                 Text::default(),
                 TitleText,
@@ -37,33 +116,79 @@ fn setup(mut commands: Commands, fonts: Res<FontsCollection>) {
                     font_size: 70.0,
                     ..default()
                 },
-                Typewriter {
-                    full_text: TITLE.to_string(),
-                    visible_chars: 0,
-                    timer: Timer::from_seconds(0.05, TimerMode::Repeating),
-                }
-            ),
-            (
-                Name::new("Play Button"),
+                typewriter(TITLE),
+            ));
+
+            for (index, (_, label)) in MODES.iter().enumerate() {
+                p.spawn((
+                    Name::new("Mode Label"),
+                    Text::default(),
+                    ModeLabel(index),
+                    TextColor(if index == focus.0 { FOCUSED } else { UNFOCUSED }),
+                    font.clone(),
+                    typewriter(label),
+                ));
+            }
+
+            p.spawn((
+                Name::new("Title Hint"),
                 Text::default(),
-                PressToPlayText,
-                TextFont {
-                    font: fonts.title.clone(),
-                    font_size: 20.0,
-                    ..default()
-                },
-                Typewriter {
-                    full_text: PRESS_TO_PLAY.to_string(),
-                    visible_chars: 0,
-                    timer: Timer::from_seconds(0.05, TimerMode::Repeating),
-                }
-            ),
-        ],
-    ));
+                PromptText(|actions| {
+                    format!(
+                        "Up and down to choose, {} to start",
+                        actions.prompt(Action::Confirm)
+                    )
+                }),
+                font,
+                typewriter(""),
+            ));
+        });
+}
+
+/// Moves the focus up and down the modes and starts the focused one on [`Action::Confirm`] or
+/// [`Nav::Select`].
+fn choose_mode(
+    mut focus: ResMut<TitleFocus>,
+    mut chosen: MessageWriter<ModeChosen>,
+    actions: Actions,
+) {
+    let count = MODES.len();
+
+    if actions.nav_just_pressed(Nav::Down) || actions.nav_just_pressed(Nav::Next) {
+        focus.0 = (focus.0 + 1) % count;
+    }
+
+    if actions.nav_just_pressed(Nav::Up) || actions.nav_just_pressed(Nav::Previous) {
+        focus.0 = (focus.0 + count - 1) % count;
+    }
+
+    if actions.just_pressed(Action::Confirm) || actions.nav_just_pressed(Nav::Select) {
+        chosen.write(ModeChosen(MODES[focus.0].0));
+    }
 }
 
-fn press_space(mut state: ResMut<NextState<AppState>>, input: Res<ButtonInput<KeyCode>>) {
-    if input.just_pressed(KeyCode::Space) {
-        state.set(AppState::Main);
+fn color_modes(focus: Res<TitleFocus>, mut labels: Query<(&ModeLabel, &mut TextColor)>) {
+    if !focus.is_changed() {
+        return;
     }
+
+    for (label, mut color) in &mut labels {
+        color.0 = if label.0 == focus.0 {
+            FOCUSED
+        } else {
+            UNFOCUSED
+        };
+    }
+}
+
+fn start_standard(
+    mut state: ResMut<NextState<AppState>>,
+    mut mode: ResMut<GameMode>,
+    mut layout: ResMut<StartingLayout>,
+    mut opponent: ResMut<Opponent>,
+) {
+    *mode = GameMode::Standard;
+    *layout = StartingLayout::default();
+    *opponent = Opponent::default();
+    state.set(AppState::Main);
 }
@@ -0,0 +1,222 @@
+//! Wave mode. Every few passes black reinforcements drop onto the free squares of black's back
+//! ranks, more and stronger each time. The score is the number of waves white survives.
+
+use crate::{
+    AppState, BoardState, GameMode,
+    actions::{Action, Actions},
+    ai::Opponent,
+    assets::{FontsCollection, SpritesBgCollection, SpritesFgCollection},
+    behaviour::*,
+    chessboard::{StartingLayout, TurnPassed, insert_piece},
+    faller::{DROP_HEIGHT, DropIn},
+    save,
+    title::{TitleMode, mode_chosen},
+};
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const SAVE_FILE: &str = "waves.ron";
+const PASSES_PER_WAVE: u32 = 3;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(save::load::<WaveRecord>(SAVE_FILE).unwrap_or_default());
+
+    app.add_systems(
+        Update,
+        start_waves.run_if(in_state(AppState::Title).and(mode_chosen(TitleMode::Waves))),
+    );
+    app.add_systems(OnEnter(AppState::Main), setup.run_if(in_wave_mode));
+    app.add_systems(
+        Update,
+        (send_waves, update_wave_text, wave_controls)
+            .chain()
+            .run_if(
+                in_state(AppState::Main)
+                    .and(in_wave_mode)
+                    .and(resource_exists::<Waves>),
+            ),
+    );
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Waves {
+    pub passes: u32,
+    /// Waves weathered so far, the starting army being the first.
+    pub survived: u32,
+    pub over: bool,
+}
+
+/// Best score so far, saved whenever it is beaten.
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+pub struct WaveRecord {
+    pub best: u32,
+}
+
+#[derive(Component)]
+struct WaveText;
+
+fn in_wave_mode(mode: Res<GameMode>) -> bool {
+    *mode == GameMode::Waves
+}
+
+/// The reinforcements of the `wave`th wave after the starting army, counting from zero, at most
+/// one per free square.
+fn wave_pieces(wave: u32, free_squares: usize, rng: &mut impl Rng) -> Vec<Piece> {
+    let mut kinds = vec![PieceKind::Pawn, PieceKind::Knight, PieceKind::Bishop];
+    if wave >= 3 {
+        kinds.push(PieceKind::Rook);
+    }
+    if wave >= 5 {
+        kinds.push(PieceKind::Queen);
+    }
+
+    let level = (1 + wave / 3).min(u32::from(MAX_LEVEL));
+    let count = (wave as usize + 2).min(free_squares);
+
+    (0..count)
+        .map(|_| {
+            let mut piece = Piece::new(PieceColor::Black, *kinds.choose(rng).unwrap());
+            piece.gain_xp((level - 1) * XP_PER_LEVEL);
+            piece
+        })
+        .collect()
+}
+
+fn start_waves(
+    mut state: ResMut<NextState<AppState>>,
+    mut mode: ResMut<GameMode>,
+    mut layout: ResMut<StartingLayout>,
+    mut opponent: ResMut<Opponent>,
+) {
+    *mode = GameMode::Waves;
+    *layout = StartingLayout::default();
+    *opponent = Opponent::Random;
+    state.set(AppState::Main);
+}
+
+/// Starts counting from scratch, so restarting doesn't carry over the last game's waves.
+fn setup(mut commands: Commands, font: Res<FontsCollection>) {
+    commands.insert_resource(Waves::default());
+    commands.spawn((
+        Name::new("Wave Panel"),
+        DespawnOnExit(AppState::Main),
+        Node {
+            position_type: PositionType::Absolute,
+            left: px(10.0),
+            bottom: px(10.0),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Name::new("Wave Text"),
+            Text::default(),
+            WaveText,
+            TextFont {
+                font: font.title.clone(),
+                font_size: 28.0,
+                ..default()
+            },
+        )],
+    ));
+}
+
+fn send_waves(
+    mut commands: Commands,
+    mut turn_passed: MessageReader<TurnPassed>,
+    mut waves: ResMut<Waves>,
+    mut record: ResMut<WaveRecord>,
+    mut chessgrid: ResMut<ChessGrid>,
+    mut board_state: ResMut<NextState<BoardState>>,
+    pieces: Query<&Piece>,
+    fg: Res<SpritesFgCollection>,
+    bg: Res<SpritesBgCollection>,
+    font: Res<FontsCollection>,
+) {
+    for _ in turn_passed.read() {
+        if waves.over {
+            continue;
+        }
+
+        waves.passes += 1;
+
+        let king_alive = chessgrid
+            .pieces
+            .iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entity| pieces.get(*entity).ok())
+            .any(|piece| piece.color == PieceColor::White && piece.kind == PieceKind::King);
+
+        if !king_alive {
+            waves.over = true;
+            board_state.set(BoardState::Finished);
+
+            if waves.survived > record.best {
+                record.best = waves.survived;
+                save::store(SAVE_FILE, &*record);
+            }
+
+            continue;
+        }
+
+        if !waves.passes.is_multiple_of(PASSES_PER_WAVE) {
+            continue;
+        }
+
+        // The king outlasted the last wave, so it counts before the next one arrives.
+        let wave = waves.survived;
+        waves.survived += 1;
+
+        let mut rng = rand::rng();
+        let mut free: Vec<GridCoords> = (0..2)
+            .flat_map(|y| (0..8).map(move |x| GridCoords::new(x, y)))
+            .filter(|coords| chessgrid.get_piece(*coords).is_none())
+            .collect();
+        free.shuffle(&mut rng);
+
+        for (coords, piece) in free.iter().zip(wave_pieces(wave, free.len(), &mut rng)) {
+            let mut square = commands.entity(chessgrid.get_square(*coords));
+            let entity = insert_piece(&mut square, piece, &fg, &bg, font.title.clone());
+
+            commands.entity(entity).insert(DropIn {
+                height: DROP_HEIGHT,
+            });
+            chessgrid.pieces[coords.0.x as usize][coords.0.y as usize] = Some(entity);
+        }
+    }
+}
+
+fn update_wave_text(
+    mut text_query: Query<&mut Text, With<WaveText>>,
+    waves: Res<Waves>,
+    record: Res<WaveRecord>,
+    actions: Actions,
+) {
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+
+    text.0 = if waves.over {
+        format!(
+            "The King has fallen!\nWaves survived: {}\nBest: {}\nPress {} to return",
+            waves.survived,
+            record.best,
+            actions.prompt(Action::Confirm)
+        )
+    } else {
+        format!(
+            "Waves survived: {}\nNext wave in {} passes\nBest: {}",
+            waves.survived,
+            PASSES_PER_WAVE - waves.passes % PASSES_PER_WAVE,
+            record.best
+        )
+    };
+}
+
+fn wave_controls(mut state: ResMut<NextState<AppState>>, actions: Actions, waves: Res<Waves>) {
+    if waves.over && actions.just_pressed(Action::Confirm) {
+        state.set(AppState::Title);
+    }
+}